@@ -1,6 +1,7 @@
 use clap::{Parser, Subcommand};
+use spinhdl_core::core::{compat, templates};
 use spinhdl_core::{BuildCfg, BuildStage};
-use std::{fs, path::PathBuf};
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "spinhdl", about = "HDL project build and generation tool")]
@@ -18,6 +19,13 @@ enum Commands {
     Weave {
         #[arg(default_value = "spinhdl.toml")]
         config: PathBuf,
+        /// Reuse one Vivado process per design instead of one per script (parallel
+        /// shell routes start their own)
+        #[arg(long)]
+        session: bool,
+        /// Continue when installed tool versions differ from [project.toolchain]
+        #[arg(long)]
+        allow_tool_mismatch: bool,
     },
 
     Spin {
@@ -28,9 +36,19 @@ enum Commands {
     Emit {
         #[arg(default_value = "spinhdl.toml")]
         config: PathBuf,
-        #[arg(long, default_value = "build/main")]
-        dir: String,
+        /// Bitstream directory, relative to the config; defaults to the root design's build dir
+        #[arg(long)]
+        dir: Option<String>,
+        /// Continue when installed tool versions differ from [project.toolchain]
+        #[arg(long)]
+        allow_tool_mismatch: bool,
     },
+    /// Render every script the flow would run into the build tree, without running Vivado
+    EmitScripts {
+        #[arg(default_value = "spinhdl.toml")]
+        config: PathBuf,
+    },
+
     Dryrun {
         #[arg(default_value = "spinhdl.toml")]
         config: PathBuf,
@@ -47,28 +65,80 @@ enum Commands {
         #[arg(default_value = "spinhdl.toml")]
         config: PathBuf,
     },
+
+    /// Check that a partial bitstream was built against a static bitstream
+    VerifyCompat {
+        static_bit: PathBuf,
+        partial: PathBuf,
+    },
+
+    /// Manage the TCL templates scripts are generated from
+    Templates {
+        #[command(subcommand)]
+        action: TemplatesAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplatesAction {
+    /// Write the built-in templates to a directory for customisation
+    Export {
+        #[arg(long, default_value = "templates")]
+        dir: PathBuf,
+        /// Overwrite templates that already exist in the directory
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Weave { config } => {
+        Commands::Weave {
+            config,
+            session,
+            allow_tool_mismatch,
+        } => {
             let mut cfg = load_config(&config);
+            cfg.projectcfg.session |= session;
+            cfg.allow_tool_mismatch = allow_tool_mismatch;
             println!("Project name: {}", cfg.projectcfg.name);
             println!("Project version: {}", cfg.projectcfg.version);
             cfg.verify_build_setup();
             cfg.build_designs();
+            cfg.print_reports();
         }
-        Commands::Emit { config, dir } => {
+        Commands::Emit {
+            config,
+            dir,
+            allow_tool_mismatch,
+        } => {
             let mut cfg = load_config(&config);
             println!("Project name: {}", cfg.projectcfg.name);
             println!("Project version: {}", cfg.projectcfg.version);
-            if let Err(e) = cfg.create_zynq_driver_tcl(&dir) {
+            cfg.allow_tool_mismatch = allow_tool_mismatch;
+            cfg.projectcfg.verify_project_setup(&cfg.base_dir);
+            cfg.verify_toolchain();
+            let dir = cfg.zynq_driver_dir(dir.as_deref());
+            let tcl = cfg.build_root().join("zynq_driver.tcl");
+            if let Err(e) = cfg.create_zynq_driver_tcl(&dir.to_string_lossy(), &tcl) {
                 panic!("Error creating zynq driver TCL {}", e);
             }
         }
 
+        Commands::EmitScripts { config } => {
+            let mut cfg = load_config(&config);
+            match cfg.emit_scripts() {
+                Ok(scripts) => {
+                    for tcl in scripts {
+                        println!("Rendered {}", tcl.display());
+                    }
+                }
+                Err(e) => panic!("Failed to render scripts {}", e),
+            }
+        }
+
         Commands::Dryrun { config } => {
             let mut cfg = load_config(&config);
             // let fg = FlowGraph::from_toml_file("build/flow.lock.toml")?;
@@ -81,8 +151,9 @@ fn main() {
                 cfg.flow_graph.topo_order().join("\n  ")
             );
             //println!("{:#?}", cfg.tasks);
-            let dot = cfg.flow_graph.to_dot();
-            std::fs::write("flow.dot", dot);
+            if let Err(e) = cfg.flow_graph.write_dot_file("flow.dot") {
+                panic!("Failed to write flow.dot {}", e);
+            }
         }
 
         Commands::Revert {
@@ -95,7 +166,7 @@ fn main() {
 
             let Some(stage_enum) = BuildStage::from_str(&stage) else {
                     panic!(
-                        "Unknown stage '{}'. Expected one of: verify_files, create_project, synth, route, bitgen.",
+                        "Unknown stage '{}'. Expected one of: verify_files, create_project, synth, route, pr_verify, bitgen.",
                         stage
                     );
             };
@@ -103,14 +174,34 @@ fn main() {
             cfg.revert_stage(&design, stage_enum);
         }
 
+        Commands::VerifyCompat {
+            static_bit,
+            partial,
+        } => match compat::verify_compat(&static_bit, &partial) {
+            Ok(id) => println!(
+                "{} matches {} (build ID {})",
+                partial.display(),
+                static_bit.display(),
+                id
+            ),
+            Err(e) => panic!("Incompatible bitstreams: {}", e),
+        },
+
+        Commands::Templates {
+            action: TemplatesAction::Export { dir, force },
+        } => {
+            if let Err(e) = templates::export_builtin(&dir, force) {
+                panic!("Failed to export templates to {}: {}", dir.display(), e);
+            }
+        }
+
         _ => {
             panic!("Command currently not supported");
         }
     }
 }
 
-fn load_config(path: &PathBuf) -> BuildCfg {
-    let data = fs::read_to_string(path)
-        .unwrap_or_else(|_| panic!("failed to read file: {}", path.display()));
-    toml::from_str(&data).expect("failed to parse toml")
+fn load_config(path: &Path) -> BuildCfg {
+    BuildCfg::from_file(path)
+        .unwrap_or_else(|e| panic!("failed to load config {}: {}", path.display(), e))
 }
@@ -1,15 +1,41 @@
 use crate::design_hier;
 use crate::flow_graph::*;
+use crate::toolchain::{Tool, ToolRegistry, ToolchainCfg};
 
 use glob::glob;
 use super::init::*;
-use serde::Deserialize;
-use std::{env, fs, fs::File, path::Path, process::Command};
-use std::{io, io::Error, io::ErrorKind};
-
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader, Error, ErrorKind, Write};
+use std::process::{Command, Stdio};
+use std::{fs, fs::File, sync::Mutex, thread};
+
+pub mod compat;
 pub mod create_tcl;
-
-#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub mod hooks;
+pub mod manifest;
+pub mod options;
+pub mod partition;
+pub mod pblock;
+pub mod pr_verify;
+pub mod reports;
+pub mod retry;
+pub mod session;
+pub mod sources;
+pub mod stamps;
+pub mod tcl;
+pub mod templates;
+pub mod timing;
+
+use hooks::HooksCfg;
+use partition::{Configuration, Partition, PrCfg};
+use retry::RetryCfg;
+use session::TclSession;
+use templates::Templates;
+use timing::TimingCfg;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum BuildTasks {
     Synth,
@@ -25,14 +51,25 @@ pub enum BuildIntrTasks {
     Bitgen,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ModuleType {
     Static,
     Recon,
 }
 
-#[derive(Debug, Deserialize)]
+/// How designs are driven through Vivado.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowMode {
+    /// `.xpr` project with `launch_runs`
+    #[default]
+    Project,
+    /// in-memory design with a checkpoint at every stage boundary
+    NonProject,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectCfg {
     pub name: String,
     pub version: String,
@@ -40,6 +77,28 @@ pub struct ProjectCfg {
     pub arch: String,
     pub part_xdc: String,
     pub build_dir: String,
+    /// Keep one `vivado -mode tcl` process per design instead of spawning a
+    /// batch process for every script; scripts of a design running in
+    /// parallel each start their own.
+    #[serde(default)]
+    pub session: bool,
+    #[serde(default)]
+    pub flow: FlowMode,
+    #[serde(default)]
+    pub timing: TimingCfg,
+    #[serde(default)]
+    pub toolchain: ToolchainCfg,
+    #[serde(default)]
+    pub retry: RetryCfg,
+    /// project-local overrides of the built-in TCL templates
+    #[serde(default = "default_templates_dir")]
+    pub templates_dir: String,
+    #[serde(default)]
+    pub hooks: HooksCfg,
+}
+
+fn default_templates_dir() -> String {
+    "templates".to_string()
 }
 
 #[derive(Debug, Deserialize)]
@@ -55,64 +114,185 @@ pub struct BuildCfg {
     pub designcfg: Vec<DesignCfg>,
     pub root: RootDesign,
     pub hier: Vec<design_hier::DesignEntry>,
+    #[serde(default)]
+    pub pr: PrCfg,
     #[serde(skip)]
     pub design_graph: design_hier::HierarchyGraph,
     #[serde(skip)]
     pub flow_graph: FlowGraph,
-}
-
-pub struct PrXdc {
-    project_name: String,
-    instance_name: String,
-    region: String,
+    /// directory of the config file; relative paths in the config resolve against it
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+    #[serde(skip)]
+    pub tools: ToolRegistry,
+    #[serde(skip)]
+    pub templates: Templates,
+    /// continue with a warning when detected tool versions differ from the pinned ones
+    #[serde(skip)]
+    pub allow_tool_mismatch: bool,
+    /// design name -> live Vivado session (only used in session mode)
+    #[serde(skip)]
+    pub sessions: Mutex<HashMap<String, TclSession>>,
 }
 
 impl ProjectCfg {
-    pub fn verify_project_setup(&self) {
-        if !Path::new(&self.part_xdc).exists() {
-            println!("Missing part_xdc file: {}", self.part_xdc);
+    pub fn verify_project_setup(&self, base_dir: &Path) {
+        let part_xdc = base_dir.join(&self.part_xdc);
+        if !part_xdc.exists() {
+            println!("Missing part_xdc file: {}", part_xdc.display());
             panic!("Required part_xdc file not found");
         }
 
-        if !Path::new(&self.build_dir).exists() {
-            println!("Creating build directory: {}", self.build_dir);
-            fs::create_dir_all(&self.build_dir).expect("Failed to create build directory");
+        if let Some(hook) = self.hooks.missing().first() {
+            println!("Missing hook file: {}", hook);
+            panic!("Required hook file not found");
+        }
+
+        let build_dir = base_dir.join(&self.build_dir);
+        if !build_dir.exists() {
+            println!("Creating build directory: {}", build_dir.display());
+            fs::create_dir_all(&build_dir).expect("Failed to create build directory");
         } else {
-            println!("Build directory already exists: {}", self.build_dir);
+            println!("Build directory already exists: {}", build_dir.display());
         }
     }
 }
 
 impl BuildCfg {
+    /// Load a config file; relative paths inside it resolve against its directory.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let data = fs::read_to_string(path)?;
+        let mut cfg: BuildCfg = toml::from_str(&data)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        cfg.base_dir = fs::canonicalize(dir)?;
+        cfg.projectcfg.retry.compile()?;
+
+        cfg.projectcfg.hooks.resolve(&cfg.base_dir);
+        for design in &mut cfg.designcfg {
+            design.hooks.resolve(&cfg.base_dir);
+        }
+        cfg.templates = Templates::load(&cfg.base_dir.join(&cfg.projectcfg.templates_dir))?;
+
+        for m in cfg.hier.iter_mut().flat_map(|h| &mut h.modules) {
+            if let Some(f) = &m.floorplan {
+                m.floorplan = Some(cfg.base_dir.join(f).to_string_lossy().into_owned());
+            } else if !m.rm.is_empty() && m.region.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Partition '{}' has no region", m.name),
+                ));
+            }
+            pblock::check_region(&m.region).map_err(|e| {
+                Error::new(ErrorKind::InvalidData, format!("Module '{}': {}", m.name, e))
+            })?;
+        }
+        if let Some(root) = &cfg.root.design {
+            cfg.resolve_partitions(root)?;
+        }
+
+        Ok(cfg)
+    }
+
+    /// Absolute path of `project.build_dir`.
+    pub fn build_root(&self) -> PathBuf {
+        self.base_dir.join(&self.projectcfg.build_dir)
+    }
+
+    /// Absolute path of the build directory of a single design.
+    pub fn design_dir(&self, design: &str) -> PathBuf {
+        self.build_root().join(design)
+    }
+
+    /// Bitstream directory the Zynq driver script points at: `dir` relative
+    /// to the config directory, or the build directory of the root design.
+    pub fn zynq_driver_dir(&self, dir: Option<&str>) -> PathBuf {
+        match dir {
+            Some(dir) => self.base_dir.join(dir),
+            None => self.design_dir(self.root.design.as_deref().unwrap_or("main")),
+        }
+    }
+
+    /// Detect the installed tools, enforce `[project.toolchain]` pins and
+    /// record what was found in `<build_dir>/build_info.toml`.
+    pub fn verify_toolchain(&mut self) {
+        self.tools = ToolRegistry::detect(&self.projectcfg.toolchain, &self.base_dir);
+
+        if let Err(e) = self.tools.check_versions(&self.projectcfg.toolchain) {
+            if self.allow_tool_mismatch {
+                eprintln!("Warning: toolchain mismatch: {}", e);
+            } else {
+                panic!("Toolchain mismatch: {} (use --allow-tool-mismatch to override)", e);
+            }
+        }
+
+        let info_path = self.build_root().join("build_info.toml");
+        if let Err(e) = self.tools.write_build_info(&info_path) {
+            panic!("Failed to write {} : {}", info_path.display(), e);
+        }
+    }
+
     pub fn verify_build_setup(&mut self) {
-        self.projectcfg.verify_project_setup();
+        self.projectcfg.verify_project_setup(&self.base_dir);
+        self.verify_toolchain();
+        self.prepare_build_tree();
         for design in &mut self.designcfg {
-            design.build_path = format!("{}/{}", self.projectcfg.build_dir, design.name);
+            design.verify_files_exist(&self.base_dir);
+        }
 
-            if !Path::new(&design.build_path).exists() {
-                println!("Creating design build directory: {}", design.build_path);
-                fs::create_dir_all(&design.build_path)
-                    .expect("Failed to create design build directory");
+        for m in self.hier.iter().flat_map(|h| &h.modules) {
+            if let Some(f) = m.floorplan.as_deref().filter(|f| !Path::new(f).exists()) {
+                println!("Missing floorplan for '{}': {}", m.name, f);
+                panic!("Required floorplan file not found");
             }
+        }
+    }
 
-            println!("Changing directory to build: {}", self.projectcfg.build_dir);
-
-            let build_dir = &design.build_path;
-            let cur_dir = env::current_dir().expect("Failed to get current directory");
+    /// Create every design build directory and resolve the design sources,
+    /// without checking that they exist.
+    pub fn prepare_build_tree(&mut self) {
+        let build_root = self.build_root();
+        for design in &mut self.designcfg {
+            let build_path = build_root.join(&design.name);
+            design.build_path = build_path.to_string_lossy().into_owned();
 
-            env::set_current_dir(build_dir).expect("Failed to change directory to build");
+            if !build_path.exists() {
+                println!("Creating design build directory: {}", design.build_path);
+                fs::create_dir_all(&build_path)
+                    .expect("Failed to create design build directory");
+            }
 
-            design.verify_files_exist();
+            design.populate_files(&self.base_dir);
+        }
+    }
 
-            env::set_current_dir(cur_dir).expect("Failed to change directory to build");
+    /// Render the scripts of every flow node into the build tree without
+    /// running any tool, returning them in flow order.
+    pub fn emit_scripts(&mut self) -> io::Result<Vec<PathBuf>> {
+        self.prepare_build_tree();
+        self.design_graph = design_hier::HierarchyGraph::new();
+        self.parse_hierarchy();
+        self.build_flow_graph();
+
+        let mut scripts = Vec::new();
+        for node in self.flow_graph.nodes_in_order() {
+            scripts.extend(self.render_node_scripts(node)?);
         }
+        Ok(scripts)
     }
 
     pub fn parse_hierarchy(&mut self) {
         for d in &self.hier {
             self.design_graph.add_design(&d.name);
             for m in &d.modules {
-                self.design_graph.add_module(&m.name, m.region.as_deref());
+                let region = (!m.region.is_empty()).then(|| m.region.join(" "));
+                self.design_graph.add_module(&m.name, region.as_deref());
                 self.design_graph.connect_design_to_module(&d.name, &m.name);
             }
         }
@@ -154,34 +334,85 @@ impl BuildCfg {
                 }
             }
 
-            let base = format!("{}/{}", self.projectcfg.build_dir, name);
+            // create_project reads the sources, synth turns them into a netlist
+            let mut project_inputs = vec![
+                format!("project.part={}", self.projectcfg.part),
+                format!("project.flow={:?}", self.projectcfg.flow),
+            ];
+            project_inputs.extend(
+                [&d.rtl_files, &d.sim_files, &d.xdc_files, &d.xci_files, &d.ip_files]
+                    .into_iter()
+                    .flatten()
+                    .map(|f| format!("{}{}", FILE_INPUT, f)),
+            );
+            let mut synth_inputs = d.synth.inputs();
+            synth_inputs.extend(d.define_list.iter().map(|v| format!("define={}", v)));
+            synth_inputs.extend(d.generic_list.iter().map(|v| format!("generic={}", v)));
+            synth_inputs.extend(d.include_dir_paths.iter().map(|v| format!("include_dir={}", v)));
+            synth_inputs.extend(self.hook_inputs(name, &["pre_synth", "post_synth"]));
+
+            for input in project_inputs {
+                self.flow_graph.add_input(name, BuildStage::CreateProject, &input);
+            }
+            for input in synth_inputs {
+                self.flow_graph.add_input(name, BuildStage::Synth, &input);
+            }
 
+            let build_root = self.build_root();
+            let base = build_root.join(name).to_string_lossy().into_owned();
+
+            match self.projectcfg.flow {
+                FlowMode::Project => {
+                    self.flow_graph.add_artifact(
+                        name,
+                        BuildStage::CreateProject,
+                        &format!("{}/create_project.tcl", base),
+                    );
+                    self.flow_graph.add_artifact(
+                        name,
+                        BuildStage::CreateProject,
+                        &format!("{}/{}.xpr", base, name),
+                    );
+                    self.flow_graph.add_artifact(
+                        name,
+                        BuildStage::CreateProject,
+                        &format!("{}/create_project.attempt*.log", base),
+                    );
+                }
+                FlowMode::NonProject => {
+                    self.flow_graph.add_artifact(
+                        name,
+                        BuildStage::CreateProject,
+                        &format!("{}/read_sources.tcl", base),
+                    );
+                }
+            }
+
+            // synth stage
             self.flow_graph.add_artifact(
                 name,
-                BuildStage::CreateProject,
-                &format!("{}/create_project.tcl", base),
+                BuildStage::Synth,
+                &format!("{}/run_synth.tcl", base),
             );
             self.flow_graph.add_artifact(
                 name,
-                BuildStage::CreateProject,
-                &format!("{}/{}.xpr", base, name),
+                BuildStage::Synth,
+                &self.synth_checkpoint(name).to_string_lossy(),
             );
-
-            // synth stage
             self.flow_graph.add_artifact(
                 name,
                 BuildStage::Synth,
-                &format!("{}/run_synth.tcl", base),
+                &format!("{}/run_synth.attempt*.log", base),
             );
             self.flow_graph.add_artifact(
                 name,
                 BuildStage::Synth,
-                &format!("{}/{}/runs/synth_1", base, name),
+                &format!("{}/reports/synth_*.rpt", base),
             );
             self.flow_graph.add_artifact(
                 name,
                 BuildStage::Synth,
-                &format!("{}/{}.dcp", self.projectcfg.build_dir, name),
+                &format!("{}/reports/{}", base, reports::SUMMARY_FILE),
             );
 
             // route stage
@@ -193,8 +424,15 @@ impl BuildCfg {
             self.flow_graph.add_artifact(
                 name,
                 BuildStage::Route,
-                &format!("{}/{}.runs", base, name),
+                &format!("{}/reports/route_*.rpt", base),
             );
+            if self.projectcfg.flow == FlowMode::Project {
+                self.flow_graph.add_artifact(
+                    name,
+                    BuildStage::Route,
+                    &format!("{}/{}.runs", base, name),
+                );
+            }
             self.flow_graph.add_artifact(
                 name,
                 BuildStage::Route,
@@ -203,7 +441,12 @@ impl BuildCfg {
             self.flow_graph.add_artifact(
                 name,
                 BuildStage::Route,
-                &format!("{}/run_route.tcl", base),
+                &format!("{}/run_route*.attempt*.log", base),
+            );
+            self.flow_graph.add_artifact(
+                name,
+                BuildStage::Route,
+                &format!("{}/run_route*.tcl", base),
             );
 
             // bitgen stage
@@ -220,53 +463,289 @@ impl BuildCfg {
                 .add_artifact(name, BuildStage::Bitgen, &format!("{}/*.prm", base));
             self.flow_graph
                 .add_artifact(name, BuildStage::Bitgen, &format!("{}/*.xsa", base));
+            self.flow_graph.add_artifact(
+                name,
+                BuildStage::Bitgen,
+                &format!("{}/run_bitgen*.attempt*.log", base),
+            );
         }
 
-        if let Some(root_design) = self.root.design.as_ref() {
+        if let Some(root_design) = self.root.design.clone() {
+            let root_design = &root_design;
             // add aditional artifacts related to main in PR
 
-            let base = format!("{}/{}", self.projectcfg.build_dir, root_design);
+            let base = self.design_dir(root_design).to_string_lossy().into_owned();
+            let generates_pr_xdc = self
+                .hier
+                .iter()
+                .filter(|h| &h.name == root_design)
+                .flat_map(|h| &h.modules)
+                .any(|m| !m.rm.is_empty() && m.floorplan.is_none());
+
+            if generates_pr_xdc {
+                self.flow_graph.add_artifact(
+                    root_design,
+                    BuildStage::Route,
+                    &format!("{}/pr_{}.xdc", base, root_design),
+                );
+                self.flow_graph.add_artifact(
+                    root_design,
+                    BuildStage::Route,
+                    &format!("{}/create_pr_xdc.tcl", base),
+                );
+                self.flow_graph.add_artifact(
+                    root_design,
+                    BuildStage::Route,
+                    &format!("{}/create_pr_xdc.attempt*.log", base),
+                );
+                if self.projectcfg.flow == FlowMode::NonProject {
+                    self.flow_graph.add_artifact(
+                        root_design,
+                        BuildStage::Route,
+                        &format!("{}/{}_pr.dcp", base, root_design),
+                    );
+                }
+            }
+
+            let root_depth = self
+                .designcfg
+                .iter()
+                .find(|d| &d.name == root_design)
+                .map(|d| d.build)
+                .unwrap_or(BuildTasks::Bitgen);
+
+            let mut rms: Vec<String> = self
+                .consumed_checkpoints()
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            rms.sort();
+            for rm in &rms {
+                let _ = self.flow_graph.ensure_node(rm, BuildStage::Synth);
+                self.flow_graph.add_artifact(
+                    rm,
+                    BuildStage::Synth,
+                    &format!("{}/{}.dcp", self.build_root().display(), rm),
+                );
+            }
+
+            if matches!(root_depth, BuildTasks::Route | BuildTasks::Bitgen) {
+                // configurations come from the partitions of the hierarchy
+                if self.design_graph.lookup.is_empty() {
+                    self.parse_hierarchy();
+                }
+                let configs = self
+                    .configurations(root_design)
+                    .unwrap_or_else(|e| panic!("Invalid configurations for {} : {}", root_design, e));
+                self.add_configuration_nodes(root_design, &configs, root_depth);
+            }
+        }
+
+        // reverting a stage forgets that it ran
+        for idx in self.flow_graph.graph.node_indices() {
+            let node = &self.flow_graph.graph[idx];
+            let stamp = stamps::stamp_path(&self.design_dir(&node.design), node);
+            self.flow_graph.graph[idx]
+                .artifacts
+                .push(stamp.to_string_lossy().into_owned());
+        }
+    }
+
+    /// `file:` inputs for the hooks of `design` sourced at `points`.
+    fn hook_inputs(&self, design: &str, points: &[&str]) -> Vec<String> {
+        self.hooks_for(design)
+            .into_iter()
+            .filter(|(point, _)| points.contains(point))
+            .flat_map(|(_, files)| files)
+            .map(|f| format!("{}{}", FILE_INPUT, f))
+            .collect()
+    }
+
+    /// Whether flow node `design:stage[:configuration]` already ran with its
+    /// current inputs, see [`stamps`].
+    pub fn is_up_to_date(&self, design: &str, stage: BuildStage, configuration: Option<&str>) -> bool {
+        let Some(node) = self.flow_graph.get_node(design, stage, configuration) else {
+            return false;
+        };
+        let deps: Vec<PathBuf> = self
+            .flow_graph
+            .dependencies(node)
+            .into_iter()
+            .map(|d| stamps::stamp_path(&self.design_dir(&d.design), d))
+            .collect();
+
+        let stamp = stamps::stamp_path(&self.design_dir(design), node);
+        let up_to_date = stamps::is_up_to_date(&stamp, node, &deps);
+        if up_to_date {
+            println!("'{}' is up to date, skipping", node.key);
+        }
+        up_to_date
+    }
+
+    /// Record that flow node `design:stage[:configuration]` ran with its
+    /// current inputs.
+    pub fn write_stamp(&self, design: &str, stage: BuildStage, configuration: Option<&str>) {
+        let Some(node) = self.flow_graph.get_node(design, stage, configuration) else {
+            return;
+        };
+        let stamp = stamps::stamp_path(&self.design_dir(design), node);
+        if let Err(e) = stamps::write_stamp(&stamp, node) {
+            eprintln!("Failed to write {} : {}", stamp.display(), e);
+        }
+    }
+
+    /// One route node, and one bitgen node when `depth` reaches it, per
+    /// configuration of `root_design`. The first configuration routes after
+    /// the PR constraints; the others start from its locked static design or
+    /// from the abstract shells it writes, and do not depend on each other.
+    fn add_configuration_nodes(
+        &mut self,
+        root_design: &str,
+        configs: &[Configuration],
+        depth: BuildTasks,
+    ) {
+        let base = self.design_dir(root_design).to_string_lossy().into_owned();
+        let pr_route = self.flow_graph.ensure_node(root_design, BuildStage::Route);
+        let pr_bitgen = (depth == BuildTasks::Bitgen)
+            .then(|| self.flow_graph.ensure_node(root_design, BuildStage::Bitgen));
+        if pr_bitgen.is_some() {
             self.flow_graph.add_artifact(
                 root_design,
-                BuildStage::Route,
-                &format!("{}/pr_main.xdc", base),
+                BuildStage::Bitgen,
+                &format!("{}/{}", base, manifest::MANIFEST_FILE),
             );
-            self.flow_graph.add_artifact(
-                root_design,
-                BuildStage::Route,
-                &format!("{}/create_pr_xdc.tcl", base),
+        }
+        let static_outputs: Vec<String> = self
+            .static_outputs(root_design)
+            .iter()
+            .map(|f| format!("{}/{}", base, f))
+            .collect();
+        let mut static_route = None;
+
+        // the PR constraints and every route follow from the partitions; the
+        // RMs a configuration places are in its own input, so adding an RM
+        // leaves the existing routes, static included, up to date
+        let partitions = self.partitions(root_design);
+        let mut pr_inputs: Vec<String> = partitions
+            .iter()
+            .map(|p| Partition { rms: Vec::new(), ..p.clone() })
+            .map(|p| format!("partition={}", serde_json::to_string(&p).unwrap_or_default()))
+            .collect();
+        pr_inputs.extend(
+            partitions
+                .iter()
+                .filter_map(|p| p.floorplan.as_ref())
+                .map(|f| format!("{}{}", FILE_INPUT, f)),
+        );
+        self.flow_graph.graph[pr_route].inputs.extend(pr_inputs.iter().cloned());
+
+        let mut route_inputs = self
+            .designcfg
+            .iter()
+            .find(|d| d.name == root_design)
+            .map(|d| d.implementation.inputs())
+            .unwrap_or_default();
+        route_inputs.push(format!("pr.abstract_shell={}", self.pr.abstract_shell));
+        route_inputs.extend(pr_inputs);
+        route_inputs.extend(self.hook_inputs(root_design, &["pre_route", "post_route"]));
+        let mut bitgen_inputs = vec![
+            format!("pr.stamp={:?}", self.pr.stamp),
+            format!("pr.interface={}", self.pr.interface),
+        ];
+        bitgen_inputs.extend(self.hook_inputs(root_design, &["pre_bitgen", "post_bitgen"]));
+
+        // configurations are checked against each other before any bitstream
+        let full = configs.iter().filter(|c| c.shell.is_none()).count();
+        let verify = (full > 1).then(|| {
+            let idx = self.flow_graph.ensure_node(root_design, BuildStage::PrVerify);
+            let artifacts = &mut self.flow_graph.graph[idx].artifacts;
+            artifacts.push(format!("{}/pr_verify.tcl", base));
+            artifacts.push(format!("{}/pr_verify.attempt*.log", base));
+            artifacts.push(format!("{}/reports/{}", base, pr_verify::PR_VERIFY_REPORT));
+            idx
+        });
+
+        for config in configs {
+            let name = Some(config.name.as_str());
+            let route = self
+                .flow_graph
+                .ensure_config_node(root_design, BuildStage::Route, name);
+            self.flow_graph
+                .depend_nodes(static_route.unwrap_or(pr_route), route);
+            // nested RMs are routed against the parents locked by an earlier configuration
+            if let Some(parent) = configs
+                .iter()
+                .find(|c| c.locked_dcp.is_some() && c.locked_dcp == config.parent_dcp)
+            {
+                let parent_route = self.flow_graph.ensure_config_node(
+                    root_design,
+                    BuildStage::Route,
+                    Some(&parent.name),
+                );
+                self.flow_graph.depend_nodes(parent_route, route);
+            }
+            for m in config.modules.iter().filter(|m| !m.greybox) {
+                let rm = self.flow_graph.ensure_node(&m.rm, BuildStage::Synth);
+                self.flow_graph.depend_nodes(rm, route);
+            }
+
+            let config_input = format!(
+                "config={}",
+                serde_json::to_string(config).unwrap_or_default()
             );
+            let inputs = &mut self.flow_graph.graph[route].inputs;
+            inputs.extend(route_inputs.iter().cloned());
+            inputs.push(config_input.clone());
+
+            let artifacts = &mut self.flow_graph.graph[route].artifacts;
+            artifacts.push(format!("{}/run_route_{}.tcl", base, config.name));
+            artifacts.push(format!("{}/run_route_{}.attempt*.log", base, config.name));
+            artifacts.push(format!("{}/{}_routed.dcp", base, config.name));
+            artifacts.push(format!("{}/reports/route_{}_*.rpt", base, config.name));
+            if let Some(locked) = &config.locked_dcp {
+                artifacts.push(format!("{}/{}", base, locked));
+            }
+            if config.implements_static {
+                artifacts.extend(static_outputs.iter().cloned());
+                artifacts.push(format!("{}/{}", base, compat::BUILD_ID_FILE));
+                static_route = Some(route);
+            }
 
-            let mut hier_by_name = std::collections::HashMap::new();
-            for h in &self.hier {
-                hier_by_name.insert(h.name.as_str(), h);
-            }
-            if let Some(root_h) = hier_by_name.get(root_design.as_str()) {
-                let root_depth = self
-                    .designcfg
-                    .iter()
-                    .find(|d| &d.name == root_design)
-                    .map(|d| d.build)
-                    .unwrap_or(BuildTasks::Bitgen);
-
-                for m in &root_h.modules {
-                    for rm in &m.rm {
-                        let _ = self.flow_graph.ensure_node(rm, BuildStage::Synth);
-                        if matches!(root_depth, BuildTasks::Route | BuildTasks::Bitgen) {
-                            self.flow_graph
-                                .depend((rm, BuildStage::Synth), (root_design, BuildStage::Route));
-                        }
-                    }
+            if let Some(verify) = verify.filter(|_| config.shell.is_none()) {
+                self.flow_graph.depend_nodes(route, verify);
+            }
+
+            if let Some(pr_bitgen) = pr_bitgen {
+                let bitgen = self
+                    .flow_graph
+                    .ensure_config_node(root_design, BuildStage::Bitgen, name);
+                self.flow_graph.depend_nodes(route, bitgen);
+                if let Some(verify) = verify {
+                    self.flow_graph.depend_nodes(verify, bitgen);
+                }
+                self.flow_graph.depend_nodes(bitgen, pr_bitgen);
+
+                let inputs = &mut self.flow_graph.graph[bitgen].inputs;
+                inputs.extend(bitgen_inputs.iter().cloned());
+                inputs.push(config_input);
+
+                let artifacts = &mut self.flow_graph.graph[bitgen].artifacts;
+                artifacts.push(format!("{}/run_bitgen_{}.tcl", base, config.name));
+                artifacts.push(format!("{}/run_bitgen_{}.attempt*.log", base, config.name));
+                artifacts.push(format!("{}/{}.bit", base, config.name));
+                artifacts.push(format!("{}/{}_pblock_*_partial.bit", base, config.name));
+                for m in config.modules.iter().filter(|m| m.writes_bin) {
+                    artifacts.push(format!("{}/{}", base, m.partial_bin));
                 }
             }
         }
     }
 
     pub fn revert_stage(&self, design: &str, stage: BuildStage) {
-        if let Some(files) = self.flow_graph.get_artifacts(design, stage) {
+        if let Some(files) = self.flow_graph.stage_artifacts(design, stage) {
             println!("Reverting stage {:?} for design {}", stage, design);
 
-            for pattern in files {
+            for pattern in &files {
                 let paths: Vec<_> = match glob(pattern) {
                     Ok(paths) => paths.filter_map(Result::ok).collect(),
                     Err(_) => vec![],
@@ -290,6 +769,9 @@ impl BuildCfg {
                     }
                 }
             }
+
+            // drop the reverted runs from the summary
+            self.collect_reports(design);
         } else {
             eprintln!(
                 "No artifacts found for design '{}' at stage {:?}",
@@ -298,186 +780,530 @@ impl BuildCfg {
         }
     }
 
-    pub fn run_tcl(&self, tcl: &str) -> io::Result<()> {
+    /// Run `tcl` with Vivado from the directory the script lives in, retrying
+    /// failures that match the stage's retry policy.
+    ///
+    /// The output of every attempt is kept in `<script>.attempt<N>.log`.
+    pub fn run_tcl(&self, design: &str, stage: BuildStage, tcl: &Path) -> io::Result<()> {
         // check if the tcl exists
-        if !Path::new(tcl).exists() {
+        if !tcl.exists() {
             return Err(Error::new(
                 ErrorKind::NotFound,
-                format!("TCL file not found: {}", tcl),
+                format!("TCL file not found: {}", tcl.display()),
             ));
         }
 
-        let status = Command::new("vivado")
-            .args(["-nojournal", "-nolog", "-mode", "batch", "-source", tcl])
-            .status()?;
+        let policy = self.projectcfg.retry.for_stage(stage);
+        let mut attempt = 1;
+
+        loop {
+            let log_path = tcl.with_extension(format!("attempt{}.log", attempt));
+            let result = self.run_tcl_once(design, tcl, &log_path);
+
+            let Err(e) = result else {
+                return Ok(());
+            };
+
+            let output = fs::read_to_string(&log_path).unwrap_or_default();
+            if !policy.should_retry(attempt, &output) {
+                return Err(e);
+            }
+
+            eprintln!(
+                "{} failed with a retryable error (attempt {}/{}): {}",
+                tcl.display(),
+                attempt,
+                policy.retries + 1,
+                e
+            );
+            attempt += 1;
+        }
+    }
+
+    fn run_tcl_once(&self, design: &str, tcl: &Path, log_path: &Path) -> io::Result<()> {
+        let mut log = File::create(log_path)?;
+
+        if self.projectcfg.session {
+            return self.run_tcl_in_session(design, tcl, &mut log);
+        }
+
+        let work_dir = tcl.parent().unwrap_or(Path::new("."));
+        let mut child = Command::new(self.tools.exe(Tool::Vivado))
+            .args(["-nojournal", "-nolog", "-mode", "batch", "-source"])
+            .arg(tcl)
+            .current_dir(work_dir)
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines() {
+                let line = line?;
+                println!("{}", line);
+                writeln!(log, "{}", line)?;
+            }
+        }
 
+        let status = child.wait()?;
         if !status.success() {
-            return Err(Error::new(ErrorKind::Other, "Vivado TCL execution failed"));
+            return Err(Error::other("Vivado TCL execution failed"));
         }
 
         Ok(())
     }
 
-    pub fn synth_designs(&self) {
-        for design in &self.designcfg {
-            let build_dir = &design.build_path;
-            let cur_dir = env::current_dir().expect("Failed to get current directory");
+    fn run_tcl_in_session(&self, design: &str, tcl: &Path, log: &mut File) -> io::Result<()> {
+        let tcl_path = fs::canonicalize(tcl)?;
+        // the table is only locked to take a session out and put it back, so
+        // scripts of one design running in parallel (the shell routes) each
+        // get a session of their own instead of waiting for the other
+        let idle = self
+            .sessions
+            .lock()
+            .map_err(|_| Error::other("Vivado session table poisoned"))?
+            .remove(design);
+
+        let mut session = match idle {
+            Some(mut s) => {
+                if s.is_alive() {
+                    s
+                } else {
+                    println!("Vivado session for '{}' died, restarting", design);
+                    TclSession::spawn(&self.tools.exe(Tool::Vivado), &self.design_dir(design))?
+                }
+            }
+            None => {
+                println!("Starting Vivado session for '{}'", design);
+                TclSession::spawn(&self.tools.exe(Tool::Vivado), &self.design_dir(design))?
+            }
+        };
 
-            env::set_current_dir(build_dir).expect("Failed to change directory to build");
+        let result = session.source(&tcl_path, log);
 
-            if let Err(e) = self.create_project_tcl(design) {
-                panic!("Failed to create project tcl for {} : {}", design.name, e);
+        // a crashed session is dropped here and respawned on next use; of
+        // sessions running in parallel only one is kept
+        if session.is_alive() {
+            self.sessions
+                .lock()
+                .map_err(|_| Error::other("Vivado session table poisoned"))?
+                .entry(design.to_string())
+                .or_insert(session);
+        }
+
+        result
+    }
+
+    pub fn close_sessions(&self) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            for (design, session) in sessions.drain() {
+                println!("Closing Vivado session for '{}'", design);
+                drop(session);
             }
+        }
+    }
 
-            println!("Running Vivado for design '{}'", design.name);
+    /// Designs whose synthesized checkpoint is consumed by another design's
+    /// route stage (the RMs under the root design, including those of
+    /// partitions nested in RMs).
+    pub fn consumed_checkpoints(&self) -> HashSet<&str> {
+        let mut rms = HashSet::new();
+        let Some(root_design) = self.root.design.as_deref() else {
+            return rms;
+        };
 
-            if let Err(e) = self.run_tcl("create_project.tcl") {
-                panic!("Vivado failed for {} : {}", design.name, e);
+        let mut pending = vec![root_design];
+        while let Some(design) = pending.pop() {
+            for rm in self
+                .hier
+                .iter()
+                .filter(|h| h.name == design)
+                .flat_map(|h| &h.modules)
+                .flat_map(|m| &m.rm)
+            {
+                if rms.insert(rm.as_str()) {
+                    pending.push(rm);
+                }
             }
+        }
+        rms
+    }
+
+    /// Checkpoint written by the synth stage of `design`.
+    pub fn synth_checkpoint(&self, design: &str) -> PathBuf {
+        let build_dir = self.design_dir(design);
+        match self.projectcfg.flow {
+            FlowMode::Project => build_dir
+                .join(design)
+                .join("runs/synth_1")
+                .join(format!("{}.dcp", design)),
+            FlowMode::NonProject => build_dir.join(format!("{}_synth.dcp", design)),
+        }
+    }
+
+    pub fn synth_designs(&self) {
+        let published = self.consumed_checkpoints();
 
-            if let Err(e) = self.create_synth_tcl(design) {
-                panic!("Failed to create run synth tcl for {} : {}", design.name, e);
+        for design in &self.designcfg {
+            let project_scripts = self
+                .render_stage_scripts(&design.name, BuildStage::CreateProject)
+                .unwrap_or_else(|e| {
+                    panic!("Failed to create project tcl for {} : {}", design.name, e)
+                });
+
+            // read_sources.tcl of the non-project flow is sourced by run_synth.tcl
+            if !self.is_up_to_date(&design.name, BuildStage::CreateProject, None) {
+                if self.projectcfg.flow == FlowMode::Project {
+                    println!("Running Vivado for design '{}'", design.name);
+
+                    for tcl in &project_scripts {
+                        if let Err(e) = self.run_tcl(&design.name, BuildStage::CreateProject, tcl) {
+                            panic!("Vivado failed for {} : {}", design.name, e);
+                        }
+                    }
+                }
+                self.write_stamp(&design.name, BuildStage::CreateProject, None);
             }
 
-            // synth
-            //if design.build == BuildTasks::Synth {
-            if let Err(e) = self.run_tcl("run_synth.tcl") {
-                panic!("Run Synth failed for {} : {}", design.name, e);
-            }
-            //}
-
-            // symlink
-            // match design.moduletype {
-            //     ModuleType::Recon => {
-            let src = format!(
-                "{}/{}/{}/runs/synth_1/{}.dcp",
-                cur_dir.to_string_lossy().to_string(),
-                build_dir,
-                design.name,
-                design.name
-            );
-            let dst = format!(
-                "{}/{}/{}.dcp",
-                cur_dir.to_string_lossy().to_string(),
-                self.projectcfg.build_dir,
-                design.name
-            );
+            let synth_scripts = self
+                .render_stage_scripts(&design.name, BuildStage::Synth)
+                .unwrap_or_else(|e| {
+                    panic!("Failed to create run synth tcl for {} : {}", design.name, e)
+                });
 
-            println!("Linking DCP: {} -> {}", src, dst);
+            // synth
+            if !self.is_up_to_date(&design.name, BuildStage::Synth, None) {
+                for tcl in &synth_scripts {
+                    if let Err(e) = self.run_tcl(&design.name, BuildStage::Synth, tcl) {
+                        panic!("Run Synth failed for {} : {}", design.name, e);
+                    }
+                }
+                self.write_stamp(&design.name, BuildStage::Synth, None);
+            }
+            self.collect_reports(&design.name);
 
-            let status = Command::new("ln")
-                .args(["-sf", &src, &dst])
-                .status()
-                .unwrap();
+            if published.contains(design.name.as_str()) {
+                let src = self.synth_checkpoint(&design.name);
+                let dst = self.build_root().join(format!("{}.dcp", design.name));
 
-            if !status.success() {
-                panic!("Failed to create symlink for {}", design.name);
+                if let Err(e) = publish_checkpoint(&src, &dst) {
+                    panic!("Failed to publish checkpoint for {} : {}", design.name, e);
+                }
             }
-            //     }
-            //     _ => {}
-            // }
 
-            env::set_current_dir(cur_dir).expect("Failed to change directory to build");
             println!("Generated TCL for design '{}'", design.name);
         }
     }
 
-    pub fn gen_bitstreams(&self, root_design: &String) -> io::Result<()> {
-        let pr_node = self.design_graph.get_child_nodes(&root_design, true);
+    /// Refresh `reports/summary.json` of `design` from its Vivado reports.
+    pub fn collect_reports(&self, design: &str) {
+        let dir = self.design_dir(design).join(reports::REPORTS_DIR);
+        if !dir.is_dir() {
+            return;
+        }
 
-        // TODO: Fix this; you are being sloppy
-        let pr_inst = match &pr_node[0] {
-            design_hier::NodeKind::Module { name, region } => PrXdc {
-                project_name: root_design.clone(),
-                instance_name: name.clone(),
-                region: region.clone().unwrap_or_default(),
-            },
-            _ => panic!("Expected Module but received Design node"),
-        };
+        if let Err(e) = reports::summarize(&dir) {
+            eprintln!("Failed to summarize reports for {} : {}", design, e);
+        }
+    }
 
-        let rm_designs = self
-            .design_graph
-            .get_child_nodes(&pr_inst.instance_name, false);
+    /// Fail the build when a routed configuration of `root_design` misses
+    /// the `[project.timing]` thresholds, printing its worst paths.
+    pub fn check_timing(&self, root_design: &str) {
+        let timing = &self.projectcfg.timing;
+        if !timing.is_enabled() {
+            return;
+        }
 
-        for rm in &rm_designs {
-            match rm {
-                design_hier::NodeKind::Design { name } => {
-                    let tcl_path = format!("run_bitgen_{}.tcl", name);
-                    if let Err(e) = self.run_tcl(&tcl_path) {
-                        panic! {"Failed to run bitstreams generation {}", e};
-                    };
-                }
-                _ => panic!("Failed to run bitstreams tcl"),
+        let dir = self.design_dir(root_design).join(reports::REPORTS_DIR);
+        let mut failed = Vec::new();
+
+        let configs = self
+            .configurations(root_design)
+            .unwrap_or_else(|e| panic!("Invalid configurations for {} : {}", root_design, e));
+
+        for config in configs {
+            let prefix = format!("route_{}", config.name);
+            let summary = reports::read_summary(&dir, &prefix);
+            let violations = timing.violations(&summary.timing);
+            if violations.is_empty() {
+                println!("Timing met for '{}' with '{}'", root_design, config.name);
+                continue;
+            }
+
+            eprintln!("Timing failed for '{}' with '{}':", root_design, config.name);
+            for v in &violations {
+                eprintln!("  {}", v);
             }
+
+            let paths = reports::report_path(&dir, &prefix, "timing_paths");
+            match fs::read_to_string(&paths) {
+                Ok(text) => eprintln!("Worst paths ({}):\n{}", paths.display(), text),
+                Err(e) => eprintln!("No worst path report {} : {}", paths.display(), e),
+            }
+            failed.push(config.name);
         }
 
-        Ok(())
+        if !failed.is_empty() {
+            panic!(
+                "Timing not met for '{}' ({}); skipping bitstream generation",
+                root_design,
+                failed.join(", ")
+            );
+        }
     }
 
-    pub fn build_designs(&mut self) {
-        // synth designs
-        self.synth_designs();
+    /// Run `pr_verify` over the routed configurations of `root_design` and
+    /// fail the build when static routing differs between them.
+    pub fn verify_configurations(&self, root_design: &str, configs: &[Configuration]) {
+        if self.is_up_to_date(root_design, BuildStage::PrVerify, None) {
+            return;
+        }
+        let scripts = self
+            .render_stage_scripts(root_design, BuildStage::PrVerify)
+            .unwrap_or_else(|e| panic!("Failed to create pr_verify script {}", e));
+        if scripts.is_empty() {
+            return;
+        }
 
-        if let Some(root_design) = self.root.design.take() {
-            // PR flow
-            self.design_graph = design_hier::HierarchyGraph::new();
+        let mut run_error = None;
+        for tcl in &scripts {
+            if let Err(e) = self.run_tcl(root_design, BuildStage::PrVerify, tcl) {
+                run_error = Some(e);
+                break;
+            }
+        }
 
-            self.parse_hierarchy();
+        let report = self
+            .design_dir(root_design)
+            .join(reports::REPORTS_DIR)
+            .join(pr_verify::PR_VERIFY_REPORT);
+        let text = fs::read_to_string(&report).unwrap_or_default();
+        let additional: Vec<String> = configs
+            .iter()
+            .filter(|c| c.shell.is_none())
+            .skip(1)
+            .map(|c| pr_verify::routed_checkpoint(&c.name))
+            .collect();
+
+        let failures = pr_verify::failures(&text, &additional);
+        if failures.is_empty() && run_error.is_none() {
+            println!("pr_verify passed for '{}'", root_design);
+            self.write_stamp(root_design, BuildStage::PrVerify, None);
+            return;
+        }
 
-            let pr_node = self.design_graph.get_child_nodes(&root_design, true);
-            println!("{:?}", pr_node);
+        eprintln!("pr_verify failed for '{}':", root_design);
+        for f in &failures {
+            eprintln!("  {}", f);
+        }
+        if let Some(e) = run_error {
+            eprintln!("  {}", e);
+        }
+        panic!(
+            "Static routing differs between configurations of '{}' (see {}); skipping bitstream generation",
+            root_design,
+            report.display()
+        );
+    }
 
-            let pr_constr = match &pr_node[0] {
-                design_hier::NodeKind::Module { name, region } => PrXdc {
-                    project_name: root_design.clone(),
-                    instance_name: name.clone(),
-                    region: region.clone().unwrap_or_default(),
-                },
-                _ => panic!("Expected Module but received Design node"),
+    /// Print the figures of every `summary.json` in the build as one table.
+    pub fn print_reports(&self) {
+        let mut rows = Vec::new();
+        for design in &self.designcfg {
+            let dir = self.design_dir(&design.name).join(reports::REPORTS_DIR);
+            let Ok(summary) = reports::load_summary(&dir) else {
+                continue;
             };
+            for (run, s) in summary {
+                rows.push((design.name.clone(), run, s));
+            }
+        }
 
-            let build_dir = format!("{}/{}", self.projectcfg.build_dir, root_design);
-            let cur_dir = env::current_dir().expect("Failed to get current directory");
-
-            env::set_current_dir(build_dir).expect("Failed to change directory to build");
+        if !rows.is_empty() {
+            println!();
+            reports::print_table(&rows);
+        }
+    }
 
-            // create pr_xdc tcl
-            if let Err(e) = self.create_pr_xdc_tcl(&pr_constr) {
-                panic! {"Failed to create PR XDC {}", e};
+    fn route_configuration(&self, root_design: &str, config: &Configuration) {
+        let name = Some(config.name.as_str());
+        if self.is_up_to_date(root_design, BuildStage::Route, name) {
+            return;
+        }
+        let scripts = self
+            .render_config_scripts(root_design, BuildStage::Route, config)
+            .unwrap_or_else(|e| panic!("Failed to create route scripts {}", e));
+        for tcl in &scripts {
+            if let Err(e) = self.run_tcl(root_design, BuildStage::Route, tcl) {
+                panic! {"Failed to run {} : {}", tcl.display(), e};
             };
+        }
+        self.write_stamp(root_design, BuildStage::Route, name);
+    }
 
-            // create a empty file to exec tcl file :
-            // TODO: fix this. may be force creation of the file.
-            let xdc_path = "pr_main.xdc";
-            if let Err(e) = File::create(xdc_path) {
-                panic! {"Failed to create pr_main.xdc file {}", e};
+    pub fn gen_bitstreams(&self, root_design: &str, configs: &[Configuration]) -> io::Result<()> {
+        for config in configs {
+            let name = Some(config.name.as_str());
+            if self.is_up_to_date(root_design, BuildStage::Bitgen, name) {
+                continue;
             }
+            for tcl in self.render_config_scripts(root_design, BuildStage::Bitgen, config)? {
+                if let Err(e) = self.run_tcl(root_design, BuildStage::Bitgen, &tcl) {
+                    panic! {"Failed to run bitstreams generation {}", e};
+                };
+            }
+            self.write_stamp(root_design, BuildStage::Bitgen, name);
+        }
 
-            // execute pr_xdc
-            if let Err(e) = self.run_tcl("create_pr_xdc.tcl") {
-                panic! {"Failed to run create PR XDC {}", e};
-            };
+        Ok(())
+    }
 
-            // create route tcl
-            if let Err(e) = self.create_route_tcl(&root_design) {
-                panic! {"Failed to create PR XDC {}", e};
-            };
+    /// Derive the build ID of `root_design` from its locked static design and
+    /// write it to `build_id.txt`, where bitgen picks it up for the stamp.
+    pub fn write_build_id(&self, root_design: &str) -> io::Result<String> {
+        let dir = self.design_dir(root_design);
+        let id = manifest::build_id(&dir.join(self.static_checkpoint(root_design)))?;
+        compat::write_build_id(&dir, &id)?;
+        println!("Build ID of '{}' is {}", root_design, id);
+        Ok(id)
+    }
 
-            // route
-            if let Err(e) = self.run_tcl("run_route.tcl") {
-                panic! {"Failed to create Route {}", e};
-            };
+    /// Record every partial bitstream of `root_design` in its `manifest.json`.
+    pub fn write_manifest(&self, root_design: &str, configs: &[Configuration]) -> io::Result<()> {
+        let dir = self.design_dir(root_design);
+        let build_id = compat::read_build_id(&dir)?;
+
+        // a partial shared by several configurations is only written by the
+        // first of them
+        let mut partials: Vec<manifest::PartialEntry> = Vec::new();
+        for config in configs {
+            for m in config.modules.iter().filter(|m| m.writes_bin) {
+                let entry = manifest::describe(
+                    &dir,
+                    manifest::PartialEntry {
+                        rm: (!m.greybox).then(|| m.rm.clone()),
+                        configuration: config.name.clone(),
+                        partition: m
+                            .instance_name
+                            .rsplit('/')
+                            .next()
+                            .unwrap_or_default()
+                            .to_string(),
+                        instance: m.instance_name.clone(),
+                        pblock: m.pblock.clone(),
+                        file: m.partial_bin.clone(),
+                        size: 0,
+                        sha256: String::new(),
+                        interface: self.pr.interface.clone(),
+                    },
+                )?;
+                partials.push(entry);
+            }
+        }
+
+        manifest::write_manifest(
+            &dir,
+            &manifest::Manifest {
+                design: root_design.to_string(),
+                build_id,
+                partials,
+            },
+        )?;
+        println!("Wrote {} for '{}'", manifest::MANIFEST_FILE, root_design);
+        Ok(())
+    }
+
+    pub fn build_designs(&mut self) {
+        self.design_graph = design_hier::HierarchyGraph::new();
+        self.parse_hierarchy();
+        // stage inputs, to skip the stages that already ran with them
+        self.build_flow_graph();
+
+        // synth designs
+        self.synth_designs();
+
+        if let Some(root_design) = self.root.design.clone() {
+            // PR flow
+            let configs = self
+                .configurations(&root_design)
+                .unwrap_or_else(|e| panic!("Invalid configurations for {} : {}", root_design, e));
+
+            // create_pr_xdc.tcl, unless every partition has a floorplan
+            let pr_scripts = self
+                .render_stage_scripts(&root_design, BuildStage::Route)
+                .unwrap_or_else(|e| panic!("Failed to create route scripts {}", e));
+
+            // route, the configuration implementing static first
+            if !self.is_up_to_date(&root_design, BuildStage::Route, None) {
+                for tcl in &pr_scripts {
+                    if let Err(e) = self.run_tcl(&root_design, BuildStage::Route, tcl) {
+                        panic! {"Failed to run {} : {}", tcl.display(), e};
+                    };
+                }
+                self.write_stamp(&root_design, BuildStage::Route, None);
+            }
+            let (shells, full): (Vec<_>, Vec<_>) =
+                configs.iter().partition(|c| c.shell.is_some());
+            for config in full {
+                self.route_configuration(&root_design, config);
+            }
+            // implementations against abstract shells only read the shells
+            thread::scope(|s| {
+                for config in shells {
+                    s.spawn(|| self.route_configuration(&root_design, config));
+                }
+            });
+            self.collect_reports(&root_design);
+            self.check_timing(&root_design);
+            self.verify_configurations(&root_design, &configs);
+            if let Err(e) = self.write_build_id(&root_design) {
+                panic!("Failed to write build ID for {} : {}", root_design, e);
+            }
 
             // bitgen
-            if let Err(e) = self.create_bitstream_tcl(&root_design) {
-                panic! {"Failed to create Route {}", e};
+            if let Err(e) = self.gen_bitstreams(&root_design, &configs) {
+                panic! {"Failed to create bitstreams {}", e};
             };
+            if let Err(e) = self.write_manifest(&root_design, &configs) {
+                panic!("Failed to write {} for {} : {}", manifest::MANIFEST_FILE, root_design, e);
+            }
+        }
 
-            if let Err(e) = self.gen_bitstreams(&root_design) {
-                panic! {"Failed to create Route {}", e};
-            };
+        self.close_sessions();
+    }
+}
 
-            env::set_current_dir(cur_dir).expect("Failed to change directory to build");
+/// Expose `src` at `dst` through a relative symlink, copying when linking is
+/// not possible, so the build dir stays relocatable.
+fn publish_checkpoint(src: &Path, dst: &Path) -> io::Result<()> {
+    if !src.exists() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("Checkpoint not found: {}", src.display()),
+        ));
+    }
+
+    if fs::symlink_metadata(dst).is_ok() {
+        fs::remove_file(dst)?;
+    }
+
+    let link_dir = dst.parent().unwrap_or(Path::new("."));
+    let target = src.strip_prefix(link_dir).unwrap_or(src);
+
+    #[cfg(unix)]
+    {
+        match std::os::unix::fs::symlink(target, dst) {
+            Ok(()) => {
+                println!("Linked DCP: {} -> {}", dst.display(), target.display());
+                return Ok(());
+            }
+            Err(e) => eprintln!("Symlink failed ({}), copying {} instead", e, src.display()),
         }
     }
+
+    fs::copy(src, dst)?;
+    println!("Copied DCP: {} -> {}", src.display(), dst.display());
+    Ok(())
 }
+
+#[cfg(test)]
+mod test_core;
@@ -0,0 +1,124 @@
+//! Build IDs tying partial bitstreams to the static design they were
+//! routed against.
+//!
+//! The ID is written to `build_id.txt` after the static route and stamped
+//! into every bitstream of the design through `BITSTREAM.CONFIG.USR_ACCESS`
+//! (a write to the AXSS configuration register) or `BITSTREAM.CONFIG.USERID`
+//! (the `UserID=` field of the `.bit` header). A partial without a stamp of
+//! its own is looked up by checksum in the `manifest.json` next to it.
+
+use super::manifest;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+
+pub const BUILD_ID_FILE: &str = "build_id.txt";
+
+/// Type 1 packet writing one word to the AXSS (USR_ACCESS) register.
+const AXSS_WRITE: [u8; 4] = [0x30, 0x01, 0xA0, 0x01];
+
+/// Bitstream option the build ID is stamped through.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StampKind {
+    #[default]
+    UsrAccess,
+    Userid,
+}
+
+pub fn write_build_id(dir: &Path, id: &str) -> io::Result<()> {
+    fs::write(dir.join(BUILD_ID_FILE), format!("{}\n", id))
+}
+
+pub fn read_build_id(dir: &Path) -> io::Result<String> {
+    Ok(fs::read_to_string(dir.join(BUILD_ID_FILE))?
+        .trim()
+        .to_string())
+}
+
+/// Value written to USR_ACCESS. SelectMAP images are bit swapped within each
+/// byte, so the swapped packet is searched too.
+pub fn usr_access(data: &[u8]) -> Option<u32> {
+    let find = |data: &[u8]| {
+        data.windows(8)
+            .find(|w| w[..4] == AXSS_WRITE)
+            .map(|w| u32::from_be_bytes([w[4], w[5], w[6], w[7]]))
+    };
+
+    find(data).or_else(|| {
+        let swapped: Vec<u8> = data.iter().map(|b| b.reverse_bits()).collect();
+        find(&swapped)
+    })
+}
+
+/// `UserID=0X...` of a `.bit` header, unless left at the all-ones default.
+pub fn userid(data: &[u8]) -> Option<u32> {
+    let key = b"UserID=0X";
+    let start = data.windows(key.len()).position(|w| w == key)? + key.len();
+    let hex: String = data[start..]
+        .iter()
+        .take_while(|b| b.is_ascii_hexdigit())
+        .map(|&b| b as char)
+        .collect();
+
+    u32::from_str_radix(&hex, 16)
+        .ok()
+        .filter(|&id| id != u32::MAX)
+}
+
+/// Build ID stamped into a bitstream, as 8 hex digits.
+pub fn stamp(data: &[u8]) -> Option<String> {
+    usr_access(data)
+        .or_else(|| userid(data))
+        .map(|id| format!("{:08x}", id))
+}
+
+/// Build ID of the partial at `path`: its own stamp, or the ID of the
+/// `manifest.json` entry with the same checksum.
+fn partial_build_id(path: &Path) -> io::Result<Option<String>> {
+    if let Some(id) = stamp(&fs::read(path)?) {
+        return Ok(Some(id));
+    }
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let Ok(m) = manifest::load_manifest(dir) else {
+        return Ok(None);
+    };
+    let sha = manifest::sha256_file(path)?;
+    Ok(m.partials
+        .iter()
+        .any(|p| p.sha256 == sha)
+        .then_some(m.build_id))
+}
+
+/// Check that `partial` was built against the static design in `static_bit`,
+/// returning their common build ID.
+pub fn verify_compat(static_bit: &Path, partial: &Path) -> io::Result<String> {
+    let missing = |path: &Path| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("No build ID found in {}", path.display()),
+        )
+    };
+
+    let static_id = stamp(&fs::read(static_bit)?).ok_or_else(|| missing(static_bit))?;
+    let partial_id = partial_build_id(partial)?.ok_or_else(|| missing(partial))?;
+
+    if static_id != partial_id {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{} has build ID {} but {} was built against {}",
+                static_bit.display(),
+                static_id,
+                partial.display(),
+                partial_id
+            ),
+        ));
+    }
+    Ok(static_id)
+}
+
+#[cfg(test)]
+mod test_compat;
@@ -0,0 +1,33 @@
+use super::*;
+
+const SYNC: [u8; 4] = [0xAA, 0x99, 0x55, 0x66];
+
+fn bitstream(header: &[u8], usr_access: Option<u32>) -> Vec<u8> {
+    let mut data = header.to_vec();
+    data.extend(SYNC);
+    if let Some(id) = usr_access {
+        data.extend(AXSS_WRITE);
+        data.extend(id.to_be_bytes());
+    }
+    data.extend([0x20, 0x00, 0x00, 0x00]);
+    data
+}
+
+#[test]
+fn test_usr_access_stamp() {
+    let data = bitstream(b"\x00\x09main;UserID=0XFFFFFFFF\x00", Some(0x0badcafe));
+    assert_eq!(stamp(&data).as_deref(), Some("0badcafe"));
+
+    // SelectMAP images are bit swapped per byte
+    let swapped: Vec<u8> = data.iter().map(|b| b.reverse_bits()).collect();
+    assert_eq!(usr_access(&swapped), Some(0x0badcafe));
+}
+
+#[test]
+fn test_userid_stamp() {
+    let data = bitstream(b"\x00\x09main;UserID=0X1234ABCD;Version=2023.2\x00", None);
+    assert_eq!(stamp(&data).as_deref(), Some("1234abcd"));
+
+    let unstamped = bitstream(b"\x00\x09main;UserID=0XFFFFFFFF\x00", None);
+    assert_eq!(stamp(&unstamped), None);
+}
@@ -1,333 +1,292 @@
 use super::*;
 
-use std::{fs::File};
-use std::{io, io::Write};
+use super::tcl::TclWriter;
+use minijinja::context;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
 
 impl BuildCfg {
+    /// The `[[design]]` entry named `name`, used as template context.
+    fn design_cfg(&self, name: &str) -> Option<&DesignCfg> {
+        self.designcfg.iter().find(|d| d.name == name)
+    }
 
-    pub fn create_project_tcl(&self, design: &DesignCfg) -> io::Result<()> {
-        let tcl_path = "create_project.tcl";
-        let mut tcl_file = File::create(&tcl_path)?;
-
-        writeln!(
-            tcl_file,
-            "create_project -force -part {} {}",
-            self.projectcfg.part, design.name
-        )?;
-
-        if !design.rtl_files.is_empty() {
-            writeln!(
-                tcl_file,
-                "add_files -fileset sources_1 {}",
-                design.rtl_files.join(" ")
-            )?;
-        }
-
-        writeln!(
-            tcl_file,
-            "set_property top {} [current_fileset]",
-            design.top
-        )?;
-
-        if !design.xdc_files.is_empty() {
-            writeln!(
-                tcl_file,
-                "add_files -fileset constrs_1 {}",
-                design.xdc_files.join(" ")
-            )?;
-        }
-
-        for file in &design.xci_files {
-            writeln!(tcl_file, "import_ip {}", file)?;
-        }
+    /// Project hooks followed by the hooks of design `name`.
+    pub(crate) fn hooks_for(&self, name: &str) -> BTreeMap<&'static str, Vec<&str>> {
+        self.projectcfg
+            .hooks
+            .merged(self.design_cfg(name).map(|d| &d.hooks))
+    }
 
-        for file in &design.ip_files {
-            writeln!(tcl_file, "source {}", file)?;
+    /// Render the scripts flow node `(design, stage)` runs into the build
+    /// tree, in the order they run. Stages without scripts render nothing.
+    pub fn render_stage_scripts(
+        &self,
+        design: &str,
+        stage: BuildStage,
+    ) -> io::Result<Vec<PathBuf>> {
+        let Some(cfg) = self.design_cfg(design) else {
+            return Ok(Vec::new());
+        };
+        let dir = self.design_dir(design);
+        let is_root = self.root.design.as_deref() == Some(design);
+
+        match stage {
+            BuildStage::CreateProject => {
+                let tcl = match self.projectcfg.flow {
+                    FlowMode::Project => {
+                        let tcl = dir.join("create_project.tcl");
+                        self.create_project_tcl(cfg, &tcl)?;
+                        tcl
+                    }
+                    FlowMode::NonProject => {
+                        let tcl = dir.join("read_sources.tcl");
+                        self.create_read_sources_tcl(cfg, &tcl)?;
+                        tcl
+                    }
+                };
+                Ok(vec![tcl])
+            }
+            BuildStage::Synth => {
+                let tcl = dir.join("run_synth.tcl");
+                self.create_synth_tcl(cfg, &tcl)?;
+                Ok(vec![tcl])
+            }
+            BuildStage::Route if is_root && self.generates_pr_xdc(design) => {
+                let pr_xdc_tcl = dir.join("create_pr_xdc.tcl");
+                self.create_pr_xdc_tcl(design, &pr_xdc_tcl)?;
+                Ok(vec![pr_xdc_tcl])
+            }
+            BuildStage::PrVerify if is_root => {
+                // implementations against abstract shells have no full static design to compare
+                let configs: Vec<_> = self
+                    .configurations(design)?
+                    .into_iter()
+                    .filter(|c| c.shell.is_none())
+                    .collect();
+                if configs.len() < 2 {
+                    return Ok(Vec::new());
+                }
+                let tcl = dir.join("pr_verify.tcl");
+                self.create_pr_verify_tcl(design, &configs, &tcl)?;
+                Ok(vec![tcl])
+            }
+            _ => Ok(Vec::new()),
         }
-
-        println!("Created create_project.tcl for '{}'", design.name);
-        Ok(())
     }
 
-    pub fn create_synth_tcl(&self, design: &DesignCfg) -> io::Result<()> {
-        let synth_tcl_path = "run_synth.tcl";
-        let mut synth_tcl = File::create(&synth_tcl_path).expect("Failed to create run_synth.tcl");
-
-        writeln!(synth_tcl, "open_project {}.xpr", design.name)?;
-
-        match design.moduletype {
-            ModuleType::Recon => {
-                writeln!(synth_tcl, "synth_design -mode out_of_context")?;
-                writeln!(
-                    synth_tcl,
-                    "write_checkpoint -force {}/runs/synth_1/{}.dcp",
-                    design.name, design.name
-                )?;
-                writeln!(synth_tcl, "close_project")?;
+    /// Render the scripts of the route or bitgen node of one configuration
+    /// of the root design.
+    pub fn render_config_scripts(
+        &self,
+        design: &str,
+        stage: BuildStage,
+        config: &Configuration,
+    ) -> io::Result<Vec<PathBuf>> {
+        let dir = self.design_dir(design);
+
+        match stage {
+            BuildStage::Route => {
+                let tcl = dir.join(format!("run_route_{}.tcl", config.name));
+                self.create_route_tcl(design, config, &tcl)?;
+                Ok(vec![tcl])
             }
-
-            ModuleType::Static => {
-                writeln!(synth_tcl, "reset_run synth_1")?;
-                writeln!(synth_tcl, "launch_runs -jobs 4 synth_1")?;
-                writeln!(synth_tcl, "wait_on_run synth_1")?;
+            BuildStage::Bitgen => {
+                let tcl = dir.join(format!("run_bitgen_{}.tcl", config.name));
+                self.create_bitstream_tcl(design, config, &tcl)?;
+                Ok(vec![tcl])
             }
+            _ => Ok(Vec::new()),
         }
-
-        Ok(())
     }
 
-    pub fn create_pr_xdc_tcl(&self, constr: &PrXdc) -> io::Result<()> {
-        // Ensure root design exists
+    /// Render the scripts of flow node `node`.
+    pub fn render_node_scripts(&self, node: &FlowNode) -> io::Result<Vec<PathBuf>> {
+        let Some(name) = &node.configuration else {
+            return self.render_stage_scripts(&node.design, node.stage);
+        };
 
-        let tcl_path = "create_pr_xdc.tcl";
+        let config = self
+            .configurations(&node.design)?
+            .into_iter()
+            .find(|c| &c.name == name)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No configuration '{}' for '{}'", name, node.design),
+                )
+            })?;
+        self.render_config_scripts(&node.design, node.stage, &config)
+    }
 
-        let mut tcl = File::create(tcl_path)?;
+    /// Name of template `name` for the configured flow.
+    fn flow_template(&self, name: &str) -> String {
+        match self.projectcfg.flow {
+            FlowMode::Project => name.to_string(),
+            FlowMode::NonProject => name.replace(".tcl", "_nonproject.tcl"),
+        }
+    }
 
-        writeln!(tcl, "open_project {}.xpr", constr.project_name)?;
-        writeln!(tcl, "open_run synth_1 -name synth_1")?;
-        writeln!(
-            tcl,
-            "set_property target_constrs_file pr_{}.xdc [current_fileset -constrset]",
-            constr.project_name
+    /// Non-project flow: script that reads every source of `design` into an
+    /// in-memory project.
+    pub fn create_read_sources_tcl(&self, design: &DesignCfg, tcl_path: &Path) -> io::Result<()> {
+        let mut tcl = TclWriter::new();
+        self.templates.render(
+            "read_sources.tcl",
+            context! { project => &self.projectcfg, design => design },
+            &mut tcl,
         )?;
+        tcl.save(tcl_path)
+    }
 
-        writeln!(tcl, "startgroup")?;
-        writeln!(tcl, "create_pblock pblock_{}", constr.instance_name)?;
-        writeln!(
-            tcl,
-            "resize_pblock pblock_{} -add {}",
-            constr.instance_name, constr.region
-        )?;
-        writeln!(
-            tcl,
-            "add_cells_to_pblock pblock_{} [get_cells [list {}]] -clear_locs",
-            constr.instance_name, constr.instance_name
+    pub fn create_project_tcl(&self, design: &DesignCfg, tcl_path: &Path) -> io::Result<()> {
+        let mut tcl = TclWriter::new();
+        self.templates.render(
+            "create_project.tcl",
+            context! { project => &self.projectcfg, design => design },
+            &mut tcl,
         )?;
-        writeln!(tcl, "endgroup")?;
+        tcl.save(tcl_path)?;
 
-        writeln!(
-            tcl,
-            "set_property SNAPPING_MODE ON [get_pblocks pblock_{}]",
-            constr.instance_name
-        )?;
-        writeln!(
-            tcl,
-            "set_property RESET_AFTER_RECONFIG 1 [get_pblocks pblock_{}]",
-            constr.instance_name
+        println!("Created create_project.tcl for '{}'", design.name);
+        Ok(())
+    }
+
+    pub fn create_synth_tcl(&self, design: &DesignCfg, tcl_path: &Path) -> io::Result<()> {
+        let mut tcl = TclWriter::new();
+        self.templates.render(
+            &self.flow_template("run_synth.tcl"),
+            context! {
+                project => &self.projectcfg,
+                design => design,
+                hooks => self.projectcfg.hooks.merged(Some(&design.hooks)),
+            },
+            &mut tcl,
         )?;
-        writeln!(
-            tcl,
-            "set_property HD.RECONFIGURABLE 1 [get_cells {}]",
-            constr.instance_name
+        tcl.save(tcl_path)
+    }
+
+    /// Pblocks of every reconfigurable partition of `root_design`.
+    pub fn create_pr_xdc_tcl(&self, root_design: &str, tcl_path: &Path) -> io::Result<()> {
+        let mut tcl = TclWriter::new();
+        self.templates.render(
+            &self.flow_template("create_pr_xdc.tcl"),
+            context! {
+                project => &self.projectcfg,
+                design => self.design_cfg(root_design),
+                partitions => self.partitions(root_design),
+            },
+            &mut tcl,
         )?;
-        writeln!(tcl, "save_constraints -force")?;
-        writeln!(tcl, "close_project")?;
+        tcl.save(tcl_path)?;
 
         println!(
             "Generated partial reconfiguration XDC for '{}'",
-            constr.project_name
+            root_design
         );
         Ok(())
     }
 
-    pub fn create_route_tcl(&self, root_design: &String) -> io::Result<()> {
-        let pr_node = self.design_graph.get_child_nodes(&root_design, true);
-
-        // TODO: Fix this; you are being sloppy
-        let pr_inst = match &pr_node[0] {
-            design_hier::NodeKind::Module { name, region } => PrXdc {
-                project_name: root_design.clone(),
-                instance_name: name.clone(),
-                region: region.clone().unwrap_or_default(),
+    /// Route one configuration; the first also locks and saves the static
+    /// design and, with `[pr] abstract_shell`, the abstract shells.
+    pub fn create_route_tcl(
+        &self,
+        root_design: &str,
+        config: &Configuration,
+        tcl_path: &Path,
+    ) -> io::Result<()> {
+        let mut tcl = TclWriter::new();
+        self.templates.render(
+            &self.flow_template("run_route.tcl"),
+            context! {
+                project => &self.projectcfg,
+                pr => &self.pr,
+                design => self.design_cfg(root_design),
+                hooks => self.hooks_for(root_design),
+                partitions => self.partitions(root_design),
+                config => config,
+                static_dcp => self.static_checkpoint(root_design),
+                pr_xdc => self.generates_pr_xdc(root_design),
             },
-            _ => panic!("Expected Module but received Design node"),
-        };
-
-        let rm_designs = self
-            .design_graph
-            .get_child_nodes(&pr_inst.instance_name, false);
-
-        let tcl_path = "run_route.tcl";
-
-        let mut tcl = File::create(tcl_path)?;
-
-        writeln!(tcl, "open_project {}.xpr", root_design)?;
-        writeln!(tcl, "open_run synth_1 -name synth_1")?;
-
-        for (i, rm) in rm_designs.iter().enumerate() {
-            match rm {
-                design_hier::NodeKind::Design { name } => {
-                    writeln!(
-                        tcl,
-                        "read_checkpoint -cell [get_cells {}] ../{}.dcp",
-                        pr_inst.instance_name, name
-                    )?;
-
-                    writeln!(tcl, "opt_design")?;
-                    writeln!(tcl, "place_design")?;
-                    writeln!(tcl, "route_design")?;
-
-                    writeln!(tcl, "write_checkpoint -force {}_routed.dcp", name)?;
-
-                    writeln!(
-                        tcl,
-                        "update_design -cell [get_cells {}] -black_box",
-                        pr_inst.instance_name
-                    )?;
-
-                    if i == 0 {
-                        // only lock in the first iter
-                        writeln!(tcl, "lock -level routing")?;
-                    }
-                }
-                _ => panic!("Received a Module when Design was expected!"),
-            }
-        }
-        writeln!(tcl, "close_project")?;
-
-        Ok(())
+            &mut tcl,
+        )?;
+        tcl.save(tcl_path)
     }
 
-    pub fn create_bitstream_tcl(&self, root_design: &String) -> io::Result<()> {
-        let pr_node = self.design_graph.get_child_nodes(&root_design, true);
-
-        // TODO: Fix this; you are being sloppy
-        let pr_inst = match &pr_node[0] {
-            design_hier::NodeKind::Module { name, region } => PrXdc {
-                project_name: root_design.clone(),
-                instance_name: name.clone(),
-                region: region.clone().unwrap_or_default(),
+    pub fn create_bitstream_tcl(
+        &self,
+        root_design: &str,
+        config: &Configuration,
+        tcl_path: &Path,
+    ) -> io::Result<()> {
+        let mut tcl = TclWriter::new();
+        self.templates.render(
+            &self.flow_template("run_bitgen.tcl"),
+            context! {
+                project => &self.projectcfg,
+                pr => &self.pr,
+                build_id_file => compat::BUILD_ID_FILE,
+                design => self.design_cfg(root_design),
+                hooks => self.hooks_for(root_design),
+                partitions => self.partitions(root_design),
+                config => config,
             },
-            _ => panic!("Expected Module but received Design node"),
-        };
-
-        let rm_designs = self
-            .design_graph
-            .get_child_nodes(&pr_inst.instance_name, false);
-
-        for rm in &rm_designs {
-            match rm {
-                design_hier::NodeKind::Design { name } => {
-                    let tcl_path = format!("run_bitgen_{}.tcl", name);
-
-                    let mut tcl = File::create(&tcl_path)?;
-
-                    // Write TCL commands
-                    writeln!(tcl, "open_project {}.xpr", root_design)?;
-                    writeln!(tcl, "open_checkpoint {}_routed.dcp", name)?;
-                    writeln!(tcl, "write_bitstream -force -bin_file {}.bit", name)?;
-                    writeln!(tcl, "write_debug_probes -force {}.ltx", name)?;
-                    writeln!(tcl, "write_hw_platform -fixed -force {}.xsa", name)?;
-                    writeln!(
-                        tcl,
-                        "write_cfgmem -force -format BIN -interface SMAPx32 \
-                         -loadbit \"up 0x0 {}_pblock_{}_partial.bit\" \"{}_part.bin\"",
-                        name, pr_inst.instance_name, name
-                    )?;
-                    writeln!(tcl, "close_design")?;
-                    writeln!(tcl, "close_project")?;
-                }
-                _ => panic!("Failed to create bitstreams tcl"),
-            }
-        }
-
-        Ok(())
+            &mut tcl,
+        )?;
+        tcl.save(tcl_path)
     }
 
-    pub fn create_zynq_driver_tcl(&self, dir: &String) -> io::Result<()> {
-        let tcl_path = "zynq_driver.tcl";
-        let mut tcl = File::create(tcl_path)?;
-
-        // Header and source imports
-        writeln!(tcl, "source ~/.tools/Xilinx/Vitis/2022.2/scripts/vitis/util/zynqmp_utils.tcl")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "# Auto-generated TCL for ZynqMP Vitis driver flow")?;
-        writeln!(tcl)?;
-
-        // Build procedure
-        writeln!(tcl, "proc build {{dir}} {{")?;
-        writeln!(tcl, "    set xsa [file join $dir logic_1.xsa]")?;
-        writeln!(tcl, "    puts \"dir: $dir\"")?;
-        writeln!(tcl, "    setws driver_build/")?;
-        writeln!(tcl, "    puts \"xsa: $xsa\"")?;
-        writeln!(tcl, "    app create -name baremetal_driver -hw $xsa -proc psu_cortexa53_0 -os standalone -lang C -template {{Empty Application(C)}}")?;
-        writeln!(tcl, "    importsources -name baremetal_driver -path drivers/baremetal/src/")?;
-        writeln!(tcl, "    app build -name baremetal_driver hw_server")?;
-        writeln!(tcl, "}}")?;
-        writeln!(tcl)?;
-
-        // boot_jtag procedure
-        writeln!(tcl, "proc boot_jtag {{}} {{")?;
-        writeln!(tcl, "    targets -set -filter {{name =~ \"PSU\"}}")?;
-        writeln!(tcl, "    mwr 0xffca0010 0x0    ;# multiboot = 0")?;
-        writeln!(tcl, "    mwr 0xff5e0200 0x0100 ;# boot mode = JTAG")?;
-        writeln!(tcl, "    rst -system")?;
-        writeln!(tcl, "}}")?;
-        writeln!(tcl)?;
-
-        // flash procedure
-        writeln!(tcl, "proc flash {{dir}} {{")?;
-        writeln!(tcl, "    connect")?;
-        writeln!(tcl, "    boot_jtag")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "    source driver_build/logic_1/hw/psu_init.tcl")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "    targets -set -nocase -filter {{name =~\"APU*\"}}")?;
-        writeln!(tcl, "    rst -system")?;
-        writeln!(tcl, "    after 3000")?;
-        writeln!(tcl, "    targets -set -nocase -filter {{name =~\"APU*\"}}")?;
-        writeln!(tcl, "    reset_apu")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "    set bitfile [file join $dir logic_1.bit]")?;
-        writeln!(tcl, "    puts \"Programming bitstream: $bitfile\"")?;
-        writeln!(tcl, "    fpga -file $bitfile")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "    targets -set -nocase -filter {{name =~\"APU*\"}}")?;
-        writeln!(tcl, "    loadhw -hw driver_build/logic_1/hw/logic_1.xsa -mem-ranges [list {{0x80000000 0xbfffffff}} {{0x400000000 0x5ffffffff}} {{0x1000000000 0x7fffffffff}}] -regs")?;
-        writeln!(tcl, "    configparams force-mem-access 1")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "    set mode [expr {{[mrd -value 0xFF5E0200] & 0xf}}]")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "    targets -set -nocase -filter {{name =~ \"*A53*#0\"}}")?;
-        writeln!(tcl, "    rst -processor")?;
-        writeln!(tcl, "    dow driver_build/logic_1/export/logic_1/sw/logic_1/boot/fsbl.elf")?;
-        writeln!(tcl, "    set bp_16_2_fsbl_bp [bpadd -addr &XFsbl_Exit]")?;
-        writeln!(tcl, "    con -block -timeout 60")?;
-        writeln!(tcl, "    bpremove $bp_16_2_fsbl_bp")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "    set part0 [file join $dir logic_1_part.bin]")?;
-        writeln!(tcl, "    set part1 [file join $dir logic_2_part.bin]")?;
-        writeln!(tcl, "    set size [file size $part0]")?;
-        writeln!(tcl, "    mwr -bin -file $part0 0x0800000000 $size")?;
-        writeln!(tcl, "    mwr -bin -file $part1 0x08000C0000 $size")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "    rst -processor")?;
-        writeln!(tcl, "    dow driver_build/baremetal_driver/Debug/baremetal_driver.elf")?;
-        writeln!(tcl, "    configparams force-mem-access 0")?;
-        writeln!(tcl, "    bpadd -addr &main")?;
-        writeln!(tcl, "    con -block -timeout 500")?;
-        writeln!(tcl, "    con")?;
-        writeln!(tcl, "}}")?;
-        writeln!(tcl)?;
+    /// Compare every routed configuration against the first one.
+    pub fn create_pr_verify_tcl(
+        &self,
+        root_design: &str,
+        configs: &[Configuration],
+        tcl_path: &Path,
+    ) -> io::Result<()> {
+        let mut tcl = TclWriter::new();
+        self.templates.render(
+            "pr_verify.tcl",
+            context! {
+                project => &self.projectcfg,
+                design => self.design_cfg(root_design),
+                initial => pr_verify::routed_checkpoint(&configs[0].name),
+                additional => configs[1..]
+                    .iter()
+                    .map(|c| pr_verify::routed_checkpoint(&c.name))
+                    .collect::<Vec<_>>(),
+                report => pr_verify::PR_VERIFY_REPORT,
+            },
+            &mut tcl,
+        )?;
+        tcl.save(tcl_path)
+    }
 
-        // CLI-like command handling
-        writeln!(tcl, "if {{[llength $argv] == 0}} {{")?;
-        writeln!(tcl, "    puts \"Usage: xsct zynq_driver.tcl <build|flash|all>\"")?;
-        writeln!(tcl, "    exit")?;
-        writeln!(tcl, "}}")?;
-        writeln!(tcl)?;
-        writeln!(tcl, "set dir \"{}\"", dir)?;
-        writeln!(tcl)?;
-        writeln!(tcl, "set cmd $argv")?;
-        writeln!(tcl, "switch -- $cmd {{")?;
-        writeln!(tcl, "    build {{ build $dir }}")?;
-        writeln!(tcl, "    flash {{ flash $dir }}")?;
-        writeln!(tcl, "    all   {{ build $dir; flash $dir }}")?;
-        writeln!(tcl, "    default {{ puts \"Unknown argument: $cmd\"; exit 1 }}")?;
-        writeln!(tcl, "}}")?;
+    pub fn create_zynq_driver_tcl(&self, dir: &str, tcl_path: &Path) -> io::Result<()> {
+        let vitis_root = self
+            .tools
+            .get(Tool::Vitis)
+            .and_then(|t| t.root.as_ref())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "Vitis installation not found; set project.toolchain.vitis_settings",
+                )
+            })?;
+
+        let mut tcl = TclWriter::new();
+        self.templates.render(
+            "zynq_driver.tcl",
+            context! {
+                project => &self.projectcfg,
+                vitis_root => vitis_root,
+                dir => dir,
+            },
+            &mut tcl,
+        )?;
+        tcl.save(tcl_path)?;
 
         println!("Generated zynq_driver.tcl successfully");
         Ok(())
     }
-
 }
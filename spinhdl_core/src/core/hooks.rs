@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// `hooks.*`: user TCL files sourced at fixed points of the generated scripts.
+///
+/// Available under both `[project]` and `[[design]]`; project hooks are
+/// sourced before design hooks.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct HooksCfg {
+    /// after the project is opened, before synthesis starts
+    pub pre_synth: Option<String>,
+    /// after synthesis completes
+    pub post_synth: Option<String>,
+    /// after the synthesized design is opened, before implementation
+    pub pre_route: Option<String>,
+    /// after `route_design`, once per routed configuration
+    pub post_route: Option<String>,
+    /// after the routed checkpoint is opened, before `write_bitstream`
+    pub pre_bitgen: Option<String>,
+    /// after all bitstream files are written
+    pub post_bitgen: Option<String>,
+}
+
+impl HooksCfg {
+    fn entries(&self) -> [(&'static str, &Option<String>); 6] {
+        [
+            ("pre_synth", &self.pre_synth),
+            ("post_synth", &self.post_synth),
+            ("pre_route", &self.pre_route),
+            ("post_route", &self.post_route),
+            ("pre_bitgen", &self.pre_bitgen),
+            ("post_bitgen", &self.post_bitgen),
+        ]
+    }
+
+    /// Make every hook path absolute relative to `base_dir`.
+    pub fn resolve(&mut self, base_dir: &Path) {
+        for hook in [
+            &mut self.pre_synth,
+            &mut self.post_synth,
+            &mut self.pre_route,
+            &mut self.post_route,
+            &mut self.pre_bitgen,
+            &mut self.post_bitgen,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *hook = base_dir.join(&*hook).to_string_lossy().into_owned();
+        }
+    }
+
+    /// Hook files that do not exist on disk.
+    pub fn missing(&self) -> Vec<&str> {
+        self.entries()
+            .into_iter()
+            .filter_map(|(_, hook)| hook.as_deref())
+            .filter(|hook| !Path::new(hook).exists())
+            .collect()
+    }
+
+    /// hook point -> files to source there, `self` before `other`
+    pub fn merged<'a>(
+        &'a self,
+        other: Option<&'a HooksCfg>,
+    ) -> BTreeMap<&'static str, Vec<&'a str>> {
+        let mut out: BTreeMap<&'static str, Vec<&'a str>> = BTreeMap::new();
+
+        for hooks in std::iter::once(self).chain(other) {
+            for (point, hook) in hooks.entries() {
+                let files = out.entry(point).or_default();
+                if let Some(hook) = hook {
+                    files.push(hook);
+                }
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod test_hooks;
@@ -0,0 +1,54 @@
+use super::*;
+use crate::test_support::{DEMO, load};
+use std::fs;
+
+fn hooks(pre_route: Option<&str>, pre_bitgen: Option<&str>) -> HooksCfg {
+    HooksCfg {
+        pre_route: pre_route.map(str::to_string),
+        pre_bitgen: pre_bitgen.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_project_hooks_before_design_hooks() {
+    let project = hooks(Some("proj_route.tcl"), None);
+    let design = hooks(Some("main_route.tcl"), Some("main_bitgen.tcl"));
+
+    let merged = project.merged(Some(&design));
+    assert_eq!(merged["pre_route"], ["proj_route.tcl", "main_route.tcl"]);
+    assert_eq!(merged["pre_bitgen"], ["main_bitgen.tcl"]);
+    // every hook point is present so templates can loop over it
+    assert!(merged["post_synth"].is_empty());
+    assert_eq!(project.merged(None)["pre_route"], ["proj_route.tcl"]);
+}
+
+#[test]
+fn test_hook_placement_in_scripts() {
+    let mut cfg = load("hooks", DEMO);
+    cfg.projectcfg.hooks = hooks(Some("/h/proj_route.tcl"), Some("/h/proj_bitgen.tcl"));
+    cfg.projectcfg.hooks.post_route = Some("/h/proj_post_route.tcl".to_string());
+    cfg.projectcfg.hooks.post_synth = Some("/h/proj_post_synth.tcl".to_string());
+    let main = cfg.designcfg.iter_mut().find(|d| d.name == "main").unwrap();
+    main.hooks = hooks(None, Some("/h/main_bitgen.tcl"));
+
+    cfg.emit_scripts().unwrap();
+    let dir = cfg.design_dir("main");
+    let route = fs::read_to_string(dir.join("run_route_logic_1.tcl")).unwrap();
+    let bitgen = fs::read_to_string(dir.join("run_bitgen_logic_1.tcl")).unwrap();
+    let synth = fs::read_to_string(dir.join("run_synth.tcl")).unwrap();
+    let rm_synth = fs::read_to_string(cfg.design_dir("logic_1").join("run_synth.tcl")).unwrap();
+    let at = |text: &str, needle: &str| {
+        text.find(needle)
+            .unwrap_or_else(|| panic!("no {} in\n{}", needle, text))
+    };
+
+    // post_synth hooks see the synthesized design open in both branches
+    assert!(at(&synth, "open_run synth_1") < at(&synth, "source /h/proj_post_synth.tcl"));
+    assert!(at(&rm_synth, "synth_design") < at(&rm_synth, "source /h/proj_post_synth.tcl"));
+    assert!(at(&route, "source /h/proj_route.tcl") < at(&route, "read_checkpoint"));
+    assert!(at(&route, "route_design") < at(&route, "source /h/proj_post_route.tcl"));
+    assert!(at(&bitgen, "source /h/proj_bitgen.tcl") < at(&bitgen, "source /h/main_bitgen.tcl"));
+    assert!(at(&bitgen, "open_checkpoint") < at(&bitgen, "source /h/proj_bitgen.tcl"));
+    assert!(at(&bitgen, "source /h/main_bitgen.tcl") < at(&bitgen, "write_bitstream"));
+}
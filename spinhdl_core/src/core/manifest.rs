@@ -0,0 +1,78 @@
+//! `manifest.json`: what the partial bitstreams of a static design are.
+//!
+//! Written next to the bitstreams once bitgen is done, so loaders and release
+//! tooling can map every `.bin` to its partition without parsing file names.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Manifest {
+    pub design: String,
+    /// identifies the static build the partials were routed against
+    pub build_id: String,
+    pub partials: Vec<PartialEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PartialEntry {
+    /// `None` for the greybox of a blank configuration
+    pub rm: Option<String>,
+    /// configuration whose bitgen wrote the file
+    pub configuration: String,
+    pub partition: String,
+    /// cell of the partition in the static design
+    pub instance: String,
+    pub pblock: String,
+    /// file name relative to the manifest
+    pub file: String,
+    pub size: u64,
+    pub sha256: String,
+    /// configuration interface the file was formatted for, e.g. `SMAPx32`
+    pub interface: String,
+}
+
+/// Hex SHA-256 of the file at `path`.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// 32-bit build ID of a static design: the leading 8 hex digits of the
+/// SHA-256 of its locked static checkpoint.
+pub fn build_id(static_dcp: &Path) -> io::Result<String> {
+    let mut id = sha256_file(static_dcp)?;
+    id.truncate(8);
+    Ok(id)
+}
+
+/// Size and checksum of `file` in `dir`, filled into `entry`.
+pub fn describe(dir: &Path, mut entry: PartialEntry) -> io::Result<PartialEntry> {
+    let path = dir.join(&entry.file);
+    entry.size = fs::metadata(&path)?.len();
+    entry.sha256 = sha256_file(&path)?;
+    Ok(entry)
+}
+
+pub fn write_manifest(dir: &Path, manifest: &Manifest) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(manifest).map_err(io::Error::other)?;
+    fs::write(dir.join(MANIFEST_FILE), json)
+}
+
+pub fn load_manifest(dir: &Path) -> io::Result<Manifest> {
+    let text = fs::read_to_string(dir.join(MANIFEST_FILE))?;
+    serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod test_manifest;
@@ -0,0 +1,49 @@
+use super::*;
+use crate::test_support::scratch_dir;
+
+#[test]
+fn test_sha256_and_build_id() {
+    let dir = scratch_dir("sha");
+    let file = dir.join("abc.dcp");
+    fs::write(&file, "abc").unwrap();
+
+    assert_eq!(
+        sha256_file(&file).unwrap(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(build_id(&file).unwrap(), "ba7816bf");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_manifest_roundtrip() {
+    let dir = scratch_dir("roundtrip");
+    fs::write(dir.join("logic_1_part.bin"), [0u8; 16]).unwrap();
+
+    let entry = describe(
+        &dir,
+        PartialEntry {
+            rm: Some("logic_1".to_string()),
+            configuration: "logic_1".to_string(),
+            partition: "rp0".to_string(),
+            instance: "rp0".to_string(),
+            pblock: "pblock_rp0".to_string(),
+            file: "logic_1_part.bin".to_string(),
+            size: 0,
+            sha256: String::new(),
+            interface: "SMAPx32".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(entry.size, 16);
+    assert_eq!(entry.sha256.len(), 64);
+
+    let manifest = Manifest {
+        design: "main".to_string(),
+        build_id: "0badcafe".to_string(),
+        partials: vec![entry],
+    };
+    write_manifest(&dir, &manifest).unwrap();
+    assert_eq!(load_manifest(&dir).unwrap(), manifest);
+    fs::remove_dir_all(dir).unwrap();
+}
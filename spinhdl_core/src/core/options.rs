@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+/// `[design.synth]`: synthesis settings.
+///
+/// `strategy` and `jobs` only apply to run-based (static) synthesis; the
+/// other settings are passed to `synth_design` in both flows.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SynthOptions {
+    #[serde(default = "default_jobs")]
+    pub jobs: u32,
+    pub strategy: Option<String>,
+    pub flatten_hierarchy: Option<String>,
+    pub directive: Option<String>,
+    /// extra arguments appended to `synth_design`, each quoted as one word,
+    /// e.g. `["-fsm_extraction", "off"]`
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+fn default_jobs() -> u32 {
+    4
+}
+
+impl Default for SynthOptions {
+    fn default() -> Self {
+        Self {
+            jobs: default_jobs(),
+            strategy: None,
+            flatten_hierarchy: None,
+            directive: None,
+            args: Vec::new(),
+        }
+    }
+}
+
+/// Settings of one implementation command.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct StepOptions {
+    pub directive: Option<String>,
+    /// extra arguments appended to the command, each quoted as one word
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// `[design.impl]`: per-step implementation settings.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ImplOptions {
+    #[serde(default)]
+    pub opt_design: StepOptions,
+    #[serde(default)]
+    pub place_design: StepOptions,
+    /// `phys_opt_design` only runs when this table is present
+    pub phys_opt_design: Option<StepOptions>,
+    #[serde(default)]
+    pub route_design: StepOptions,
+}
+
+impl SynthOptions {
+    /// `key=value` lines describing the settings, recorded as stage inputs.
+    pub fn inputs(&self) -> Vec<String> {
+        let mut out = vec![format!("synth.jobs={}", self.jobs)];
+        push_opt(&mut out, "synth.strategy", &self.strategy);
+        push_opt(&mut out, "synth.flatten_hierarchy", &self.flatten_hierarchy);
+        push_opt(&mut out, "synth.directive", &self.directive);
+        if !self.args.is_empty() {
+            out.push(format!("synth.args={}", self.args.join(" ")));
+        }
+        out
+    }
+}
+
+impl ImplOptions {
+    /// `key=value` lines describing the settings, recorded as stage inputs.
+    pub fn inputs(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        let steps = [
+            ("opt_design", Some(&self.opt_design)),
+            ("place_design", Some(&self.place_design)),
+            ("phys_opt_design", self.phys_opt_design.as_ref()),
+            ("route_design", Some(&self.route_design)),
+        ];
+
+        for (step, opts) in steps {
+            let Some(opts) = opts else { continue };
+            if step == "phys_opt_design" {
+                out.push("impl.phys_opt_design=enabled".to_string());
+            }
+            push_opt(
+                &mut out,
+                &format!("impl.{}.directive", step),
+                &opts.directive,
+            );
+            if !opts.args.is_empty() {
+                out.push(format!("impl.{}.args={}", step, opts.args.join(" ")));
+            }
+        }
+        out
+    }
+}
+
+fn push_opt(out: &mut Vec<String>, key: &str, value: &Option<String>) {
+    if let Some(v) = value {
+        out.push(format!("{}={}", key, v));
+    }
+}
+
+#[cfg(test)]
+mod test_options;
@@ -0,0 +1,115 @@
+use super::*;
+use crate::test_support::{DEMO, load};
+
+#[test]
+fn test_synth_inputs() {
+    assert_eq!(SynthOptions::default().inputs(), vec!["synth.jobs=4"]);
+
+    let opts = SynthOptions {
+        jobs: 8,
+        strategy: Some("Flow_PerfOptimized_high".to_string()),
+        flatten_hierarchy: Some("rebuilt".to_string()),
+        directive: None,
+        args: vec!["-retiming".to_string(), "-fsm_extraction off".to_string()],
+    };
+    assert_eq!(
+        opts.inputs(),
+        vec![
+            "synth.jobs=8",
+            "synth.strategy=Flow_PerfOptimized_high",
+            "synth.flatten_hierarchy=rebuilt",
+            "synth.args=-retiming -fsm_extraction off",
+        ]
+    );
+}
+
+#[test]
+fn test_impl_inputs() {
+    assert!(ImplOptions::default().inputs().is_empty());
+
+    let opts = ImplOptions {
+        place_design: StepOptions {
+            directive: Some("Explore".to_string()),
+            args: Vec::new(),
+        },
+        phys_opt_design: Some(StepOptions::default()),
+        route_design: StepOptions {
+            directive: None,
+            args: vec!["-tns_cleanup".to_string()],
+        },
+        ..Default::default()
+    };
+    assert_eq!(
+        opts.inputs(),
+        vec![
+            "impl.place_design.directive=Explore",
+            "impl.phys_opt_design=enabled",
+            "impl.route_design.args=-tns_cleanup",
+        ]
+    );
+}
+
+#[test]
+fn test_options_render_into_scripts() {
+    use crate::core::FlowMode;
+    use std::fs;
+
+    let options = r#"defines = { FAST = true }
+synth = { flatten_hierarchy = "none", directive = "AreaOptimized_high", args = ["-retiming", "-fsm_extraction", "off"] }
+impl = { place_design = { directive = "Explore" }, phys_opt_design = {}, route_design = { args = ["-tns_cleanup", "[exec id]"] } }
+"#;
+    let body = DEMO.replacen("defines = { FAST = true }\n", options, 1);
+
+    for (name, flow) in [
+        ("options_project", FlowMode::Project),
+        ("options_nonproject", FlowMode::NonProject),
+    ] {
+        let mut cfg = load(name, &body);
+        cfg.projectcfg.flow = flow;
+        cfg.emit_scripts().unwrap();
+        let dir = cfg.design_dir("main");
+
+        let synth = fs::read_to_string(dir.join("run_synth.tcl")).unwrap();
+        let route = fs::read_to_string(dir.join("run_route_logic_1.tcl")).unwrap();
+        match flow {
+            FlowMode::Project => {
+                assert!(
+                    synth.contains("STEPS.SYNTH_DESIGN.ARGS.FLATTEN_HIERARCHY none"),
+                    "{}",
+                    synth
+                );
+                assert!(
+                    synth.contains("STEPS.SYNTH_DESIGN.ARGS.DIRECTIVE AreaOptimized_high"),
+                    "{}",
+                    synth
+                );
+                assert!(
+                    synth.contains("-value {-retiming -fsm_extraction off}"),
+                    "{}",
+                    synth
+                );
+                assert!(synth.contains("launch_runs -jobs 4 synth_1"), "{}", synth);
+            }
+            FlowMode::NonProject => {
+                assert!(
+                    synth.contains("-flatten_hierarchy none -directive AreaOptimized_high -verilog_define FAST -retiming -fsm_extraction off\n"),
+                    "{}",
+                    synth
+                );
+            }
+        }
+        assert!(route.contains("\nopt_design\n"), "{}", route);
+        assert!(
+            route.contains("\nplace_design -directive Explore\n"),
+            "{}",
+            route
+        );
+        assert!(route.contains("\nphys_opt_design\n"), "{}", route);
+        // extra arguments are quoted like every other value
+        assert!(
+            route.contains("\nroute_design -tns_cleanup {[exec id]}\n"),
+            "{}",
+            route
+        );
+    }
+}
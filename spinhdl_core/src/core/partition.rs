@@ -0,0 +1,640 @@
+use super::*;
+
+use compat::StampKind;
+use pblock::PblockCfg;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A reconfigurable partition: a `[[hier.modules]]` entry with `rm` designs.
+///
+/// Partitions inside an RM are nested: their cell is under the parent
+/// partition and they only exist while a hosting RM is loaded there.
+#[derive(Debug, Clone, Serialize)]
+pub struct Partition {
+    /// cell path of the partition in the static design, e.g. `rp0/sub`
+    pub instance_name: String,
+    /// `pblock_<instance_name>` with `/` replaced by `_`
+    pub pblock_name: String,
+    /// site ranges of the pblock
+    pub ranges: Vec<String>,
+    pub pblock: PblockCfg,
+    /// user XDC with the pblock; no pblock is generated when set
+    pub floorplan: Option<String>,
+    /// reconfigurable modules implemented in the partition
+    pub rms: Vec<String>,
+    /// enclosing partition of a nested partition
+    pub parent: Option<String>,
+    pub parent_pblock: Option<String>,
+    /// RMs of the parent that contain this partition
+    pub hosts: Vec<String>,
+    /// abstract shell of the partition written by the static route when
+    /// `[pr] abstract_shell` is set
+    pub shell_dcp: String,
+}
+
+impl Partition {
+    /// Whether the partition exists given the RMs picked so far.
+    fn is_active(&self, picks: &HashMap<&str, &str>) -> bool {
+        match &self.parent {
+            None => true,
+            Some(parent) => picks
+                .get(parent.as_str())
+                .is_some_and(|rm| self.hosts.iter().any(|h| h == rm)),
+        }
+    }
+}
+
+fn pblock_name(instance: &str) -> String {
+    format!("pblock_{}", instance.replace('/', "_"))
+}
+
+/// Name of the configuration that greyboxes every partition.
+pub const BLANK_CONFIG: &str = "blank";
+
+/// `[pr]`: DFX settings of the root design.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PrCfg {
+    /// configurations routed in order; derived from the partitions when empty
+    #[serde(default)]
+    pub configuration: Vec<ConfigurationCfg>,
+    /// also route a `blank` configuration with every partition greyboxed,
+    /// giving a safe partial per partition to load before swapping RMs
+    #[serde(default)]
+    pub blanking: bool,
+    /// `write_cfgmem -interface` of the partial `.bin` files
+    #[serde(default = "default_interface")]
+    pub interface: String,
+    /// bitstream option carrying the static build ID
+    #[serde(default)]
+    pub stamp: StampKind,
+    /// route only the first configuration in full and implement every other
+    /// RM on its own against an abstract shell of its partition
+    #[serde(default)]
+    pub abstract_shell: bool,
+}
+
+impl Default for PrCfg {
+    fn default() -> Self {
+        Self {
+            configuration: Vec::new(),
+            blanking: false,
+            interface: default_interface(),
+            stamp: StampKind::default(),
+            abstract_shell: false,
+        }
+    }
+}
+
+fn default_interface() -> String {
+    "SMAPx32".to_string()
+}
+
+/// A `[[pr.configuration]]` entry.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ConfigurationCfg {
+    pub name: String,
+    /// partition instance -> RM placed in it
+    pub partitions: BTreeMap<String, String>,
+}
+
+/// The reconfigurable module placed in one partition by a configuration.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigModule {
+    pub instance_name: String,
+    /// empty for a greybox
+    pub rm: String,
+    /// black box with buffered ports instead of an RM
+    pub greybox: bool,
+    pub pblock: String,
+    /// enclosing partition of a nested partition
+    pub parent: Option<String>,
+    /// partial bitstream Vivado writes for the partition
+    pub partial_bit: String,
+    /// `<rm>_part.bin`, with the instance added when the RM is used by
+    /// several partitions
+    pub partial_bin: String,
+    /// whether this configuration writes `partial_bin`; only the first
+    /// configuration placing the module does
+    pub writes_bin: bool,
+    /// the RM contains nested partitions, so its partition is subdivided
+    /// (or opened locked) instead of read whole
+    pub hosts_nested: bool,
+}
+
+/// A parent RM implemented by a configuration: `pr_subdivide` turns the
+/// partition into the static part of the RM plus the partitions nested in it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Subdivision {
+    /// cell of the parent partition
+    pub cell: String,
+    /// RM whose synthesized checkpoint fills the partition
+    pub rm: String,
+    /// partitions nested in the RM
+    pub subcells: Vec<String>,
+}
+
+/// One routed implementation of the static design with an RM in every partition.
+///
+/// Its routed checkpoint is `<name>_routed.dcp` and its bitstreams are
+/// `<name>.bit` plus one `<name>_pblock_<instance>_partial.bit` per partition.
+/// A configuration implemented against an abstract shell has a single
+/// partition and only the partial bitstream.
+#[derive(Debug, Clone, Serialize)]
+pub struct Configuration {
+    pub name: String,
+    pub modules: Vec<ConfigModule>,
+    /// routes the static design, which the other configurations reuse
+    /// locked from `<design>_static_routed.dcp`
+    pub implements_static: bool,
+    /// abstract shell opened instead of the locked static design
+    pub shell: Option<String>,
+    /// parent RMs this configuration implements first, then locks into
+    /// `locked_dcp`
+    pub subdivisions: Vec<Subdivision>,
+    /// `<name>_parents_locked.dcp` when there are `subdivisions`
+    pub locked_dcp: Option<String>,
+    /// locked checkpoint of an earlier configuration that implemented the
+    /// parent RMs placed here, opened instead of the locked static design
+    pub parent_dcp: Option<String>,
+}
+
+impl Configuration {
+    fn new(name: String, picks: Vec<(&Partition, &String)>, partitions: &[Partition]) -> Self {
+        let modules = picks
+            .into_iter()
+            .map(|(p, rm)| {
+                let shared = partitions.iter().filter(|o| o.rms.contains(rm)).count() > 1;
+                ConfigModule {
+                    instance_name: p.instance_name.clone(),
+                    rm: rm.clone(),
+                    greybox: false,
+                    pblock: p.pblock_name.clone(),
+                    parent: p.parent.clone(),
+                    partial_bit: format!("{}_{}_partial.bit", name, p.pblock_name),
+                    partial_bin: if shared {
+                        format!("{}_{}_part.bin", rm, p.instance_name.replace('/', "_"))
+                    } else {
+                        format!("{}_part.bin", rm)
+                    },
+                    writes_bin: true,
+                    hosts_nested: partitions.iter().any(|n| {
+                        n.parent.as_ref() == Some(&p.instance_name) && n.hosts.contains(rm)
+                    }),
+                }
+            })
+            .collect();
+
+        Configuration {
+            name,
+            modules,
+            implements_static: false,
+            shell: None,
+            subdivisions: Vec::new(),
+            locked_dcp: None,
+            parent_dcp: None,
+        }
+    }
+}
+
+/// Configurations when none are listed: the n-th one takes the n-th RM of
+/// every partition it contains, or its last RM when the partition has fewer.
+pub fn default_configurations(partitions: &[Partition]) -> Vec<Configuration> {
+    let count = partitions.iter().map(|p| p.rms.len()).max().unwrap_or(0);
+
+    (0..count)
+        .map(|i| {
+            let mut active: HashMap<&str, &str> = HashMap::new();
+            let mut picks: Vec<(&Partition, &String)> = Vec::new();
+            for p in partitions {
+                if !p.is_active(&active) {
+                    continue;
+                }
+                let rm = &p.rms[i.min(p.rms.len() - 1)];
+                active.insert(&p.instance_name, rm);
+                picks.push((p, rm));
+            }
+
+            let name = picks
+                .iter()
+                .map(|(_, rm)| rm.as_str())
+                .collect::<Vec<_>>()
+                .join("_");
+
+            Configuration::new(name, picks, partitions)
+        })
+        .collect()
+}
+
+/// Greybox of partition `p` in configuration `config`; its partial is
+/// `blank_<instance>_part.bin`.
+fn greybox_module(p: &Partition, config: &str) -> ConfigModule {
+    ConfigModule {
+        instance_name: p.instance_name.clone(),
+        rm: String::new(),
+        greybox: true,
+        pblock: p.pblock_name.clone(),
+        parent: None,
+        partial_bit: format!("{}_{}_partial.bit", config, p.pblock_name),
+        partial_bin: format!("{}_{}_part.bin", BLANK_CONFIG, p.instance_name),
+        writes_bin: true,
+        hosts_nested: false,
+    }
+}
+
+/// Configuration with every top-level partition greyboxed, which also
+/// removes the partitions nested in them; its partials are
+/// `blank_<instance>_part.bin`.
+pub fn blank_configuration(partitions: &[Partition]) -> Configuration {
+    let modules = partitions
+        .iter()
+        .filter(|p| p.parent.is_none())
+        .map(|p| greybox_module(p, BLANK_CONFIG))
+        .collect();
+
+    Configuration {
+        name: BLANK_CONFIG.to_string(),
+        modules,
+        implements_static: false,
+        shell: None,
+        subdivisions: Vec::new(),
+        locked_dcp: None,
+        parent_dcp: None,
+    }
+}
+
+/// One `<instance>_<rm>` configuration per RM that `static_config` does not
+/// place, implemented against the abstract shell of its partition, plus an
+/// `<instance>_blank` greybox per partition with `blanking`.
+pub fn shell_configurations(
+    partitions: &[Partition],
+    static_config: &Configuration,
+    blanking: bool,
+) -> Vec<Configuration> {
+    let mut configs = Vec::new();
+
+    for p in partitions {
+        let prefix = p.instance_name.replace('/', "_");
+        let placed = static_config
+            .modules
+            .iter()
+            .find(|m| m.instance_name == p.instance_name)
+            .map(|m| m.rm.as_str());
+
+        for rm in p.rms.iter().filter(|rm| Some(rm.as_str()) != placed) {
+            let mut config =
+                Configuration::new(format!("{}_{}", prefix, rm), vec![(p, rm)], partitions);
+            config.shell = Some(p.shell_dcp.clone());
+            configs.push(config);
+        }
+
+        if blanking {
+            let name = format!("{}_{}", prefix, BLANK_CONFIG);
+            configs.push(Configuration {
+                modules: vec![greybox_module(p, &name)],
+                name,
+                implements_static: false,
+                shell: Some(p.shell_dcp.clone()),
+                subdivisions: Vec::new(),
+                locked_dcp: None,
+                parent_dcp: None,
+            });
+        }
+    }
+    configs
+}
+
+/// Leave each partial `.bin` to the first configuration that has it, so a
+/// partial shared by several configurations is written once.
+fn assign_bin_writers(configs: &mut [Configuration]) {
+    let mut written = HashSet::new();
+    for m in configs.iter_mut().flat_map(|c| &mut c.modules) {
+        m.writes_bin = written.insert(m.partial_bin.clone());
+    }
+}
+
+/// Implement every parent RM once: the first configuration placing it
+/// subdivides its partition and locks the result, and later configurations
+/// placing it route their nested RMs against that locked checkpoint. A
+/// configuration can start from the parents locked by one configuration only.
+pub fn assign_subdivisions(
+    configs: &mut [Configuration],
+    partitions: &[Partition],
+) -> io::Result<()> {
+    let invalid = |msg: String| Error::new(ErrorKind::InvalidData, msg);
+    // (cell, rm) -> configuration that locked it
+    let mut locked_by: HashMap<(String, String), String> = HashMap::new();
+
+    for config in configs.iter_mut() {
+        let parents: Vec<(String, String)> = config
+            .modules
+            .iter()
+            .filter(|m| m.hosts_nested)
+            .map(|m| (m.instance_name.clone(), m.rm.clone()))
+            .collect();
+
+        let earlier: BTreeSet<&String> = parents.iter().filter_map(|k| locked_by.get(k)).collect();
+        if earlier.len() > 1 {
+            let names: Vec<&str> = earlier.iter().map(|s| s.as_str()).collect();
+            return Err(invalid(format!(
+                "Configuration '{}' places parent RMs locked by different configurations ({})",
+                config.name,
+                names.join(", ")
+            )));
+        }
+        if let Some(source) = earlier.first().map(|s| s.to_string()) {
+            if let Some(((cell, rm), _)) = locked_by
+                .iter()
+                .find(|(k, by)| **by == source && !parents.contains(k))
+            {
+                return Err(invalid(format!(
+                    "Configuration '{}' starts from '{}', which has '{}' locked in '{}'",
+                    config.name, source, rm, cell
+                )));
+            }
+            config.parent_dcp = Some(locked_checkpoint(&source));
+        }
+
+        for (cell, rm) in parents {
+            if locked_by.contains_key(&(cell.clone(), rm.clone())) {
+                continue;
+            }
+            let subcells = partitions
+                .iter()
+                .filter(|p| p.parent.as_ref() == Some(&cell) && p.hosts.contains(&rm))
+                .map(|p| p.instance_name.clone())
+                .collect();
+            config.subdivisions.push(Subdivision {
+                cell: cell.clone(),
+                rm: rm.clone(),
+                subcells,
+            });
+            locked_by.insert((cell, rm), config.name.clone());
+        }
+        if !config.subdivisions.is_empty() {
+            config.locked_dcp = Some(locked_checkpoint(&config.name));
+        }
+    }
+    Ok(())
+}
+
+/// Checkpoint with the parent RMs implemented by configuration `name` locked.
+fn locked_checkpoint(name: &str) -> String {
+    format!("{}_parents_locked.dcp", name)
+}
+
+/// Check `[[pr.configuration]]` entries against the partitions: every
+/// partition present in the configuration gets exactly one of its own RMs
+/// and names are unique.
+pub fn resolve_configurations(
+    partitions: &[Partition],
+    entries: &[ConfigurationCfg],
+) -> io::Result<Vec<Configuration>> {
+    let invalid = |msg: String| Error::new(ErrorKind::InvalidData, msg);
+    let mut names = HashSet::new();
+    let mut configs = Vec::new();
+
+    for entry in entries {
+        if !names.insert(entry.name.as_str()) {
+            return Err(invalid(format!("Duplicate configuration '{}'", entry.name)));
+        }
+
+        if let Some(unknown) = entry
+            .partitions
+            .keys()
+            .find(|inst| !partitions.iter().any(|p| &p.instance_name == *inst))
+        {
+            return Err(invalid(format!(
+                "Configuration '{}' names unknown partition '{}'",
+                entry.name, unknown
+            )));
+        }
+
+        let mut active: HashMap<&str, &str> = HashMap::new();
+        let mut picks = Vec::new();
+        for p in partitions {
+            let rm = entry.partitions.get(&p.instance_name);
+            if !p.is_active(&active) {
+                if rm.is_some() {
+                    return Err(invalid(format!(
+                        "Configuration '{}' places an RM in '{}', which its RM in '{}' does not contain",
+                        entry.name,
+                        p.instance_name,
+                        p.parent.as_deref().unwrap_or_default()
+                    )));
+                }
+                continue;
+            }
+
+            let Some(rm) = rm else {
+                return Err(invalid(format!(
+                    "Configuration '{}' has no RM for partition '{}'",
+                    entry.name, p.instance_name
+                )));
+            };
+            if !p.rms.contains(rm) {
+                return Err(invalid(format!(
+                    "Configuration '{}' places '{}' in '{}', which only implements {}",
+                    entry.name,
+                    rm,
+                    p.instance_name,
+                    p.rms.join(", ")
+                )));
+            }
+            active.insert(&p.instance_name, rm);
+            picks.push((p, rm));
+        }
+
+        configs.push(Configuration::new(entry.name.clone(), picks, partitions));
+    }
+
+    Ok(configs)
+}
+
+impl BuildCfg {
+    /// Reconfigurable partitions of `design`, each followed by the
+    /// partitions nested in its RMs; modules without RMs are static.
+    ///
+    /// `from_file` has already checked the partitions of the root design,
+    /// see [`BuildCfg::resolve_partitions`].
+    pub fn partitions(&self, design: &str) -> Vec<Partition> {
+        self.resolve_partitions(design).unwrap_or_default()
+    }
+
+    /// [`BuildCfg::partitions`], failing when a partition is nested in
+    /// itself through the RMs placed in it.
+    pub fn resolve_partitions(&self, design: &str) -> io::Result<Vec<Partition>> {
+        let mut out = Vec::new();
+        self.collect_partitions(design, None, &mut out)?;
+        Ok(out)
+    }
+
+    fn collect_partitions(
+        &self,
+        design: &str,
+        parent: Option<&Partition>,
+        out: &mut Vec<Partition>,
+    ) -> io::Result<()> {
+        // declaration order, which decides the configurations and the static one
+        let modules: Vec<&String> = self
+            .hier
+            .iter()
+            .filter(|h| h.name == design)
+            .flat_map(|h| &h.modules)
+            .map(|m| &m.name)
+            .collect();
+
+        for name in modules {
+            let rms = self.module_rms(name);
+            if rms.is_empty() {
+                continue;
+            }
+
+            let instance_name = match parent {
+                Some(p) => {
+                    if p.instance_name.split('/').any(|n| n == name.as_str()) {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!(
+                                "Partition '{}' is nested in itself under '{}'",
+                                name, p.instance_name
+                            ),
+                        ));
+                    }
+                    format!("{}/{}", p.instance_name, name)
+                }
+                None => name.clone(),
+            };
+
+            // the same partition reached through another RM of its parent
+            if let Some(existing) = out.iter_mut().find(|p| p.instance_name == instance_name) {
+                existing.hosts.push(design.to_string());
+                continue;
+            }
+
+            let entry = self
+                .hier
+                .iter()
+                .flat_map(|h| &h.modules)
+                .find(|m| &m.name == name);
+            let partition = Partition {
+                pblock_name: pblock_name(&instance_name),
+                shell_dcp: format!("{}_shell.dcp", instance_name.replace('/', "_")),
+                instance_name,
+                ranges: entry.map(|m| m.region.clone()).unwrap_or_default(),
+                pblock: entry.map(|m| m.pblock.clone()).unwrap_or_default(),
+                floorplan: entry.and_then(|m| m.floorplan.clone()),
+                rms,
+                parent: parent.map(|p| p.instance_name.clone()),
+                parent_pblock: parent.map(|p| p.pblock_name.clone()),
+                hosts: parent.map(|_| vec![design.to_string()]).unwrap_or_default(),
+            };
+
+            out.push(partition.clone());
+            for rm in &partition.rms {
+                self.collect_partitions(rm, Some(&partition), out)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// RMs of `module` in the order its `rm` lists declare them.
+    fn module_rms(&self, module: &str) -> Vec<String> {
+        let mut rms: Vec<String> = Vec::new();
+        for rm in self
+            .hier
+            .iter()
+            .flat_map(|h| &h.modules)
+            .filter(|m| m.name == module)
+            .flat_map(|m| &m.rm)
+        {
+            if !rms.contains(rm) {
+                rms.push(rm.clone());
+            }
+        }
+        rms
+    }
+
+    /// Configurations routed for `design`, from `[[pr.configuration]]` or
+    /// derived from its partitions.
+    ///
+    /// The first configuration implements the static design for the others.
+    /// With `[pr] abstract_shell` the others are replaced by one
+    /// configuration per remaining RM, implemented against abstract shells.
+    pub fn configurations(&self, design: &str) -> io::Result<Vec<Configuration>> {
+        let partitions = self.resolve_partitions(design)?;
+        let mut configs = if self.pr.configuration.is_empty() {
+            default_configurations(&partitions)
+        } else {
+            resolve_configurations(&partitions, &self.pr.configuration)?
+        };
+
+        if let Some(first) = configs.first_mut() {
+            first.implements_static = true;
+        }
+
+        if self.pr.abstract_shell && !configs.is_empty() {
+            if let Some(nested) = partitions.iter().find(|p| p.parent.is_some()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "[pr] abstract_shell does not support nested partition '{}'",
+                        nested.instance_name
+                    ),
+                ));
+            }
+            configs.truncate(1);
+            let shells = shell_configurations(&partitions, &configs[0], self.pr.blanking);
+            configs.extend(shells);
+            assign_bin_writers(&mut configs);
+            return Ok(configs);
+        }
+
+        // greyboxes start from the static design, so they need an RM configuration first
+        if self.pr.blanking && !configs.is_empty() {
+            if configs.iter().any(|c| c.name == BLANK_CONFIG) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Configuration '{}' is reserved for [pr] blanking",
+                        BLANK_CONFIG
+                    ),
+                ));
+            }
+            configs.push(blank_configuration(&partitions));
+        }
+        assign_subdivisions(&mut configs, &partitions)?;
+        assign_bin_writers(&mut configs);
+        Ok(configs)
+    }
+
+    /// Whether a top-level partition of `design` needs its pblock generated
+    /// by `create_pr_xdc.tcl`. Nested pblocks are created while routing.
+    pub fn generates_pr_xdc(&self, design: &str) -> bool {
+        self.partitions(design)
+            .iter()
+            .any(|p| p.parent.is_none() && p.floorplan.is_none())
+    }
+
+    /// Locked, routed static design the later configurations start from.
+    pub fn static_checkpoint(&self, design: &str) -> String {
+        format!("{}_static_routed.dcp", design)
+    }
+
+    /// Checkpoints the static route of `design` leaves for the other
+    /// configurations: the locked static design and, with
+    /// `[pr] abstract_shell`, the shell of every top-level partition.
+    pub fn static_outputs(&self, design: &str) -> Vec<String> {
+        let mut outputs = vec![self.static_checkpoint(design)];
+        if self.pr.abstract_shell {
+            outputs.extend(
+                self.partitions(design)
+                    .into_iter()
+                    .filter(|p| p.parent.is_none())
+                    .map(|p| p.shell_dcp),
+            );
+        }
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod test_partition;
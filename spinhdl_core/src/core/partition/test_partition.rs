@@ -0,0 +1,545 @@
+use super::*;
+use crate::test_support::{DEMO, load};
+
+fn partition(name: &str, rms: &[&str]) -> Partition {
+    Partition {
+        instance_name: name.to_string(),
+        pblock_name: pblock_name(name),
+        ranges: Vec::new(),
+        pblock: PblockCfg::default(),
+        floorplan: None,
+        rms: rms.iter().map(|s| s.to_string()).collect(),
+        parent: None,
+        parent_pblock: None,
+        hosts: Vec::new(),
+        shell_dcp: format!("{}_shell.dcp", name.replace('/', "_")),
+    }
+}
+
+fn nested(parent: &Partition, name: &str, hosts: &[&str], rms: &[&str]) -> Partition {
+    let instance = format!("{}/{}", parent.instance_name, name);
+    Partition {
+        parent: Some(parent.instance_name.clone()),
+        parent_pblock: Some(parent.pblock_name.clone()),
+        hosts: hosts.iter().map(|s| s.to_string()).collect(),
+        ..partition(&instance, rms)
+    }
+}
+
+fn entry(name: &str, picks: &[(&str, &str)]) -> ConfigurationCfg {
+    ConfigurationCfg {
+        name: name.to_string(),
+        partitions: picks
+            .iter()
+            .map(|(p, rm)| (p.to_string(), rm.to_string()))
+            .collect(),
+    }
+}
+
+#[test]
+fn test_default_configurations_pad_with_last_rm() {
+    let parts = [partition("rp0", &["a", "b"]), partition("rp1", &["c"])];
+    let configs = default_configurations(&parts);
+
+    let names: Vec<_> = configs.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["a_c", "b_c"]);
+    assert_eq!(configs[1].modules[1].rm, "c");
+    assert_eq!(
+        configs[1].modules[0].partial_bit,
+        "b_c_pblock_rp0_partial.bit"
+    );
+    assert_eq!(configs[1].modules[0].partial_bin, "b_part.bin");
+}
+
+#[test]
+fn test_listed_configurations() {
+    let parts = [partition("rp0", &["a", "b"]), partition("rp1", &["c", "a"])];
+    let configs = resolve_configurations(
+        &parts,
+        &[
+            entry("full", &[("rp0", "b"), ("rp1", "a")]),
+            entry("alt", &[("rp0", "a"), ("rp1", "c")]),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(configs[0].name, "full");
+    assert_eq!(configs[0].modules[0].rm, "b");
+    // "a" fits both partitions, so its partial needs the instance in its name
+    assert_eq!(configs[0].modules[1].partial_bin, "a_rp1_part.bin");
+    assert_eq!(configs[1].modules[1].rm, "c");
+}
+
+#[test]
+fn test_invalid_configurations() {
+    let parts = [partition("rp0", &["a", "b"]), partition("rp1", &["c"])];
+    let err = |entries: &[ConfigurationCfg]| {
+        resolve_configurations(&parts, entries)
+            .unwrap_err()
+            .to_string()
+    };
+
+    assert!(err(&[entry("x", &[("rp0", "a")])]).contains("no RM for partition 'rp1'"));
+    assert!(err(&[entry("x", &[("rp0", "c"), ("rp1", "c")])]).contains("only implements a, b"));
+    assert!(
+        err(&[entry("x", &[("rp0", "a"), ("rp1", "c"), ("rp9", "a")])])
+            .contains("unknown partition 'rp9'")
+    );
+    let ok = entry("x", &[("rp0", "a"), ("rp1", "c")]);
+    let again = entry("x", &[("rp0", "b"), ("rp1", "c")]);
+    assert!(err(&[ok, again]).contains("Duplicate configuration 'x'"));
+}
+
+#[test]
+fn test_blank_configuration() {
+    let parts = [partition("rp0", &["a", "b"]), partition("rp1", &["c"])];
+    let blank = blank_configuration(&parts);
+
+    assert_eq!(blank.name, BLANK_CONFIG);
+    assert!(blank.modules.iter().all(|m| m.greybox && m.rm.is_empty()));
+    assert_eq!(blank.modules[1].partial_bin, "blank_rp1_part.bin");
+}
+
+#[test]
+fn test_nested_configurations() {
+    let rp0 = partition("rp0", &["a", "b"]);
+    let sub = nested(&rp0, "sub", &["a"], &["c", "d"]);
+    let parts = [rp0, sub];
+
+    let names: Vec<_> = default_configurations(&parts)
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(names, ["a_c", "b"]);
+
+    let configs =
+        resolve_configurations(&parts, &[entry("x", &[("rp0", "a"), ("rp0/sub", "d")])]).unwrap();
+    assert_eq!(configs[0].modules[1].pblock, "pblock_rp0_sub");
+    assert_eq!(configs[0].modules[1].parent.as_deref(), Some("rp0"));
+    assert_eq!(
+        configs[0].modules[1].partial_bit,
+        "x_pblock_rp0_sub_partial.bit"
+    );
+
+    let err = |entries: &[ConfigurationCfg]| {
+        resolve_configurations(&parts, entries)
+            .unwrap_err()
+            .to_string()
+    };
+    assert!(err(&[entry("x", &[("rp0", "a")])]).contains("no RM for partition 'rp0/sub'"));
+    assert!(err(&[entry("x", &[("rp0", "b"), ("rp0/sub", "c")])]).contains("does not contain"));
+    assert_eq!(blank_configuration(&parts).modules.len(), 1);
+}
+
+#[test]
+fn test_shell_configurations() {
+    let parts = [partition("rp0", &["a", "b"]), partition("rp1", &["c", "a"])];
+    let full = &default_configurations(&parts)[0];
+    let shells = shell_configurations(&parts, full, true);
+
+    let names: Vec<_> = shells.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["rp0_b", "rp0_blank", "rp1_a", "rp1_blank"]);
+    assert!(
+        shells
+            .iter()
+            .all(|c| c.modules.len() == 1 && !c.implements_static)
+    );
+    assert_eq!(shells[2].shell.as_deref(), Some("rp1_shell.dcp"));
+    assert_eq!(shells[2].modules[0].partial_bin, "a_rp1_part.bin");
+    assert_eq!(shells[3].modules[0].partial_bin, "blank_rp1_part.bin");
+}
+
+#[test]
+fn test_partitions_follow_declaration_order() {
+    let hier = r#"
+[[hier]]
+name = "main"
+[[hier.modules]]
+name = "rp0"
+region = "SLICE_X0Y0:SLICE_X10Y10"
+rm = ["logic_1", "logic_2"]
+[[hier.modules]]
+name = "ctrl"
+[[hier.modules]]
+name = "rp1"
+region = "SLICE_X20Y0:SLICE_X30Y10"
+rm = ["logic_2", "logic_1"]
+
+[[hier]]
+name = "logic_1"
+[[hier.modules]]
+name = "sub"
+region = "SLICE_X0Y0:SLICE_X4Y4"
+rm = ["logic_2"]
+"#;
+    let body = format!("{}{}", &DEMO[..DEMO.find("[[hier]]").unwrap()], hier);
+    let cfg = load("declaration_order", &body);
+
+    let parts = cfg.partitions("main");
+    let names: Vec<_> = parts.iter().map(|p| p.instance_name.as_str()).collect();
+    assert_eq!(names, ["rp0", "rp0/sub", "rp1", "rp1/sub"]);
+    assert_eq!(parts[0].rms, ["logic_1", "logic_2"]);
+    assert_eq!(parts[1].hosts, ["logic_1"]);
+    assert_eq!(parts[2].rms, ["logic_2", "logic_1"]);
+
+    // the first RM of every partition implements static
+    let configs = cfg.configurations("main").unwrap();
+    assert_eq!(configs[0].name, "logic_1_logic_2_logic_2");
+    assert!(configs[0].implements_static);
+    assert_eq!(configs[1].name, "logic_2_logic_1_logic_2");
+    assert!(!configs[1].implements_static);
+}
+
+#[test]
+fn test_floorplan_partitions_skip_pr_xdc() {
+    let hier = r#"
+[[hier]]
+name = "shell"
+[[hier.modules]]
+name = "rp0"
+region = "SLICE_X0Y0:SLICE_X10Y10"
+rm = ["logic_1", "logic_2"]
+[[hier.modules]]
+name = "rp1"
+floorplan = "xdc/rp1.xdc"
+rm = ["logic_2"]
+"#;
+    let designs = DEMO[..DEMO.find("[[hier]]").unwrap()].replace("\"main\"", "\"shell\"");
+
+    for (name, flow) in [
+        ("floorplan_project", FlowMode::Project),
+        ("floorplan_nonproject", FlowMode::NonProject),
+    ] {
+        let mut cfg = load(name, &format!("{}{}", designs, hier));
+        cfg.projectcfg.flow = flow;
+        cfg.emit_scripts().unwrap();
+        let dir = cfg.design_dir("shell");
+
+        // only rp0 gets a generated pblock, in the XDC named after the root design
+        let pr_xdc = fs::read_to_string(dir.join("create_pr_xdc.tcl")).unwrap();
+        assert!(pr_xdc.contains("create_pblock pblock_rp0"), "{}", pr_xdc);
+        assert!(!pr_xdc.contains("rp1"), "{}", pr_xdc);
+        assert!(pr_xdc.contains("pr_shell.xdc"), "{}", pr_xdc);
+        assert!(!pr_xdc.contains("pr_main.xdc"), "{}", pr_xdc);
+
+        // the static route reads the user floorplan instead
+        let route = fs::read_to_string(dir.join("run_route_logic_1_logic_2.tcl")).unwrap();
+        let floorplan = cfg.base_dir.join("xdc/rp1.xdc");
+        assert!(
+            route.contains(&format!("read_xdc {}", floorplan.display())),
+            "{}",
+            route
+        );
+    }
+
+    // nothing to generate when every partition has a floorplan
+    let all = hier.replace(
+        "region = \"SLICE_X0Y0:SLICE_X10Y10\"",
+        "floorplan = \"xdc/rp0.xdc\"",
+    );
+    let mut cfg = load("floorplan_all", &format!("{}{}", designs, all));
+    assert!(!cfg.generates_pr_xdc("shell"));
+    let scripts = cfg.emit_scripts().unwrap();
+    assert!(
+        scripts.iter().all(|s| !s.ends_with("create_pr_xdc.tcl")),
+        "{:?}",
+        scripts
+    );
+}
+
+#[test]
+fn test_shared_partials_written_once() {
+    let parts = [partition("rp0", &["a", "b"]), partition("rp1", &["c"])];
+    let mut configs = default_configurations(&parts);
+    configs.push(blank_configuration(&parts));
+    assign_bin_writers(&mut configs);
+
+    let writers: Vec<Vec<(&str, bool)>> = configs
+        .iter()
+        .map(|c| {
+            c.modules
+                .iter()
+                .map(|m| (m.partial_bin.as_str(), m.writes_bin))
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        writers,
+        [
+            vec![("a_part.bin", true), ("c_part.bin", true)],
+            vec![("b_part.bin", true), ("c_part.bin", false)],
+            vec![("blank_rp0_part.bin", true), ("blank_rp1_part.bin", true)],
+        ]
+    );
+}
+
+#[test]
+fn test_shared_partial_bitgen_scripts() {
+    let hier = r#"
+[[hier.modules]]
+name = "rp1"
+region = "SLICE_X20Y0:SLICE_X30Y10"
+rm = ["logic_2"]
+"#;
+    let mut cfg = load("shared_partial", &format!("{}{}", DEMO, hier));
+    cfg.emit_scripts().unwrap();
+    let dir = cfg.design_dir("main");
+
+    let cfgmem = |config: &str| -> Vec<String> {
+        fs::read_to_string(dir.join(format!("run_bitgen_{}.tcl", config)))
+            .unwrap()
+            .lines()
+            .filter(|l| l.starts_with("write_cfgmem"))
+            .map(|l| l.rsplit(' ').next().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(
+        cfgmem("logic_1_logic_2"),
+        ["logic_1_part.bin", "logic_2_rp1_part.bin"]
+    );
+    assert_eq!(cfgmem("logic_2_logic_2"), ["logic_2_rp0_part.bin"]);
+}
+
+#[test]
+fn test_parent_rms_implemented_once() {
+    let rp0 = partition("rp0", &["a", "b"]);
+    let sub = nested(&rp0, "sub", &["a"], &["c", "d"]);
+    let parts = [rp0, sub];
+
+    let mut configs = resolve_configurations(
+        &parts,
+        &[
+            entry("x", &[("rp0", "a"), ("rp0/sub", "c")]),
+            entry("y", &[("rp0", "a"), ("rp0/sub", "d")]),
+            entry("z", &[("rp0", "b")]),
+        ],
+    )
+    .unwrap();
+    assign_subdivisions(&mut configs, &parts).unwrap();
+
+    assert!(configs[0].modules[0].hosts_nested);
+    assert!(!configs[0].modules[1].hosts_nested);
+    assert_eq!(configs[0].subdivisions.len(), 1);
+    assert_eq!(configs[0].subdivisions[0].cell, "rp0");
+    assert_eq!(configs[0].subdivisions[0].rm, "a");
+    assert_eq!(configs[0].subdivisions[0].subcells, ["rp0/sub"]);
+    assert_eq!(
+        configs[0].locked_dcp.as_deref(),
+        Some("x_parents_locked.dcp")
+    );
+    assert_eq!(configs[0].parent_dcp, None);
+
+    // `a` is only implemented by x
+    assert!(configs[1].subdivisions.is_empty());
+    assert_eq!(configs[1].locked_dcp, None);
+    assert_eq!(
+        configs[1].parent_dcp.as_deref(),
+        Some("x_parents_locked.dcp")
+    );
+
+    assert!(!configs[2].modules[0].hosts_nested);
+    assert!(configs[2].subdivisions.is_empty());
+    assert_eq!(configs[2].parent_dcp, None);
+}
+
+#[test]
+fn test_parents_locked_by_different_configurations() {
+    let rp0 = partition("rp0", &["a"]);
+    let rp1 = partition("rp1", &["b", "e"]);
+    let sub0 = nested(&rp0, "sub", &["a"], &["c"]);
+    let sub1 = nested(&rp1, "sub", &["b"], &["d"]);
+    let parts = [rp0, sub0, rp1, sub1];
+    let picks = |rp1: &'static str| -> Vec<(&'static str, &'static str)> {
+        let mut picks = vec![("rp0", "a"), ("rp0/sub", "c"), ("rp1", rp1)];
+        if rp1 == "b" {
+            picks.push(("rp1/sub", "d"));
+        }
+        picks
+    };
+
+    // y starts from x, which has `a` and `b` locked, without placing `b`
+    let mut configs =
+        resolve_configurations(&parts, &[entry("x", &picks("b")), entry("y", &picks("e"))])
+            .unwrap();
+    let err = assign_subdivisions(&mut configs, &parts)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("'y' starts from 'x', which has 'b' locked in 'rp1'"),
+        "{}",
+        err
+    );
+
+    // `a` locked by x and `b` by y cannot be combined
+    let mut configs = resolve_configurations(
+        &parts,
+        &[
+            entry("x", &[("rp0", "a"), ("rp0/sub", "c"), ("rp1", "e")]),
+            entry(
+                "y",
+                &[
+                    ("rp0", "a"),
+                    ("rp0/sub", "c"),
+                    ("rp1", "b"),
+                    ("rp1/sub", "d"),
+                ],
+            ),
+            entry(
+                "z",
+                &[
+                    ("rp0", "a"),
+                    ("rp0/sub", "c"),
+                    ("rp1", "b"),
+                    ("rp1/sub", "d"),
+                ],
+            ),
+        ],
+    )
+    .unwrap();
+    let err = assign_subdivisions(&mut configs, &parts)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("'z' places parent RMs locked by different configurations (x, y)"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_nested_route_scripts_subdivide_parent_once() {
+    let nested = r#"
+[[design]]
+name = "logic_3"
+top = "rm"
+rtl_dir = "rtl"
+rtl = "rm3.v"
+xdc_dir = ""
+xdc = ""
+xci_dir = ""
+xci = ""
+ip_dir = ""
+ip = ""
+build = "synth"
+moduletype = "recon"
+
+[[hier]]
+name = "logic_1"
+[[hier.modules]]
+name = "sub"
+region = "SLICE_X2Y2:SLICE_X4Y4"
+rm = ["logic_2", "logic_3"]
+
+[[pr.configuration]]
+name = "x"
+partitions = { rp0 = "logic_1", "rp0/sub" = "logic_2" }
+
+[[pr.configuration]]
+name = "y"
+partitions = { rp0 = "logic_1", "rp0/sub" = "logic_3" }
+
+[[pr.configuration]]
+name = "z"
+partitions = { rp0 = "logic_2" }
+"#;
+    let mut cfg = load("nested_routes", &format!("{}{}", DEMO, nested));
+    cfg.emit_scripts().unwrap();
+    let dir = cfg.design_dir("main");
+    let read =
+        |config: &str| fs::read_to_string(dir.join(format!("run_route_{}.tcl", config))).unwrap();
+    let count = |text: &str, cmd: &str| text.lines().filter(|l| l.starts_with(cmd)).count();
+
+    // x routes static with rp0 greyboxed, then subdivides logic_1 once
+    let x = read("x");
+    assert_eq!(count(&x, "pr_subdivide"), 1, "{}", x);
+    assert!(
+        x.contains("pr_subdivide -cell rp0 -subcells rp0/sub ../logic_1.dcp"),
+        "{}",
+        x
+    );
+    assert!(
+        !x.contains("read_checkpoint -cell [get_cells rp0] "),
+        "{}",
+        x
+    );
+    assert!(
+        x.contains("read_checkpoint -cell [get_cells rp0/sub] ../logic_2.dcp"),
+        "{}",
+        x
+    );
+    assert!(
+        x.find("write_checkpoint -force main_static_routed.dcp") < x.find("pr_subdivide"),
+        "{}",
+        x
+    );
+    assert!(
+        x.contains("write_checkpoint -force x_parents_locked.dcp"),
+        "{}",
+        x
+    );
+    assert_eq!(count(&x, "route_design"), 2, "{}", x);
+
+    // y reuses the locked parent and only implements its nested RM
+    let y = read("y");
+    assert!(
+        y.starts_with("open_project main.xpr\nopen_checkpoint x_parents_locked.dcp\n"),
+        "{}",
+        y
+    );
+    assert!(!y.contains("pr_subdivide"), "{}", y);
+    assert!(!y.contains("logic_1.dcp"), "{}", y);
+    assert!(
+        y.contains("read_checkpoint -cell [get_cells rp0/sub] ../logic_3.dcp"),
+        "{}",
+        y
+    );
+    assert_eq!(count(&y, "route_design"), 1, "{}", y);
+
+    let z = read("z");
+    assert!(
+        z.contains("open_checkpoint main_static_routed.dcp"),
+        "{}",
+        z
+    );
+    assert!(!z.contains("pr_subdivide"), "{}", z);
+}
+
+#[test]
+fn test_partition_nested_in_itself() {
+    use crate::core::BuildCfg;
+
+    let nested = |rm: &str| {
+        format!(
+            "{}\n[[hier]]\nname = \"logic_1\"\n[[hier.modules]]\nname = \"sub\"\nregion = \"SLICE_X2Y2:SLICE_X4Y4\"\nrm = [\"{}\"]\n",
+            DEMO, rm
+        )
+    };
+
+    let mut cfg = load("nested_cycle", &nested("logic_2"));
+    assert!(cfg.configurations("main").is_ok());
+    let sub = cfg
+        .hier
+        .iter_mut()
+        .flat_map(|h| &mut h.modules)
+        .find(|m| m.name == "sub")
+        .unwrap();
+    sub.rm = vec!["logic_1".to_string()];
+    let err = cfg.configurations("main").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(
+        err.to_string()
+            .contains("Partition 'sub' is nested in itself under 'rp0/sub'"),
+        "{}",
+        err
+    );
+
+    let path = cfg.base_dir.join("spinhdl.toml");
+    let text = fs::read_to_string(&path)
+        .unwrap()
+        .replace("rm = [\"logic_2\"]\n", "rm = [\"logic_1\"]\n");
+    fs::write(&path, text).unwrap();
+    assert_eq!(
+        BuildCfg::from_file(&path).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+}
@@ -0,0 +1,110 @@
+//! Pblock of a reconfigurable partition: the site ranges it covers and the
+//! properties set on it.
+//!
+//! A range is `<TYPE>_X<x>Y<y>:<TYPE>_X<x>Y<y>` (or a single site) with TYPE
+//! one of SLICE, RAMB18, RAMB36, DSP48 (DSP48E1/E2) or CLOCKREGION, both ends
+//! of the same type and the first end at or below-left of the second.
+
+use regex::Regex;
+use serde::de::Deserializer;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnappingMode {
+    Off,
+    #[default]
+    On,
+    Routing,
+}
+
+/// `pblock` table of a `[[hier.modules]]` entry.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PblockCfg {
+    pub snapping_mode: SnappingMode,
+    pub reset_after_reconfig: bool,
+    /// keep the partition's routing inside the pblock
+    pub contain_routing: bool,
+    /// keep static logic out of the pblock
+    pub exclude_placement: bool,
+}
+
+impl Default for PblockCfg {
+    fn default() -> Self {
+        Self {
+            snapping_mode: SnappingMode::On,
+            reset_after_reconfig: true,
+            contain_routing: false,
+            exclude_placement: false,
+        }
+    }
+}
+
+/// `region` as a list of ranges, or one string of whitespace separated ranges.
+pub fn parse_region<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Region {
+        One(String),
+        List(Vec<String>),
+    }
+
+    Ok(match Region::deserialize(deserializer)? {
+        Region::One(s) => s.split_whitespace().map(str::to_string).collect(),
+        Region::List(ranges) => ranges,
+    })
+}
+
+/// Site type and coordinates of `site`, e.g. `("RAMB36", 0, 4)`.
+fn parse_site(site: &str) -> Result<(&str, u32, u32), String> {
+    let re = Regex::new(r"^(SLICE|RAMB18|RAMB36|DSP48(?:E1|E2)?|CLOCKREGION)_X(\d+)Y(\d+)$")
+        .expect("valid site regex");
+    let caps = re.captures(site).ok_or_else(|| {
+        format!(
+            "'{}' is not a SLICE, RAMB18, RAMB36, DSP48 or CLOCKREGION site",
+            site
+        )
+    })?;
+
+    let coord = |i: usize| {
+        caps[i]
+            .parse()
+            .map_err(|_| format!("'{}' has an out of range coordinate", site))
+    };
+    Ok((caps.get(1).map_or("", |m| m.as_str()), coord(2)?, coord(3)?))
+}
+
+/// Check that `range` is well-formed and its corners are ordered.
+pub fn check_range(range: &str) -> Result<(), String> {
+    let Some((from, to)) = range.split_once(':') else {
+        return parse_site(range).map(|_| ());
+    };
+
+    let (from_kind, x0, y0) = parse_site(from)?;
+    let (to_kind, x1, y1) = parse_site(to)?;
+    if from_kind != to_kind {
+        return Err(format!(
+            "range '{}' mixes {} and {} sites",
+            range, from_kind, to_kind
+        ));
+    }
+    if x0 > x1 || y0 > y1 {
+        return Err(format!(
+            "range '{}' must run from the lower left to the upper right corner",
+            range
+        ));
+    }
+    Ok(())
+}
+
+/// Check every range of a region.
+pub fn check_region(ranges: &[String]) -> Result<(), String> {
+    ranges.iter().try_for_each(|r| check_range(r))
+}
+
+#[cfg(test)]
+mod test_pblock;
@@ -0,0 +1,63 @@
+use super::*;
+
+#[test]
+fn test_check_range() {
+    assert!(check_range("SLICE_X0Y0:SLICE_X10Y59").is_ok());
+    assert!(check_range("RAMB36_X0Y0:RAMB36_X0Y11").is_ok());
+    assert!(check_range("DSP48E2_X0Y0:DSP48E2_X1Y23").is_ok());
+    assert!(check_range("CLOCKREGION_X1Y2").is_ok());
+
+    assert!(
+        check_range("SLICE_X0Y0:RAMB18_X1Y1")
+            .unwrap_err()
+            .contains("mixes")
+    );
+    assert!(
+        check_range("SLICE_X10Y0:SLICE_X0Y9")
+            .unwrap_err()
+            .contains("lower left")
+    );
+    assert!(
+        check_range("SLICE_X0Y9:SLICE_X10Y0")
+            .unwrap_err()
+            .contains("lower left")
+    );
+    assert!(
+        check_range("URAM288_X0Y0:URAM288_X0Y3")
+            .unwrap_err()
+            .contains("not a SLICE")
+    );
+    assert!(check_range("SLICE_X0Y0:").is_err());
+    assert!(check_range("slice_x0y0").is_err());
+}
+
+#[test]
+fn test_region_forms() {
+    #[derive(Deserialize)]
+    struct Module {
+        #[serde(deserialize_with = "parse_region")]
+        region: Vec<String>,
+        #[serde(default)]
+        pblock: PblockCfg,
+    }
+
+    let one: Module =
+        toml::from_str(r#"region = "SLICE_X0Y0:SLICE_X9Y9 RAMB18_X0Y0:RAMB18_X0Y3""#).unwrap();
+    assert_eq!(
+        one.region,
+        ["SLICE_X0Y0:SLICE_X9Y9", "RAMB18_X0Y0:RAMB18_X0Y3"]
+    );
+    assert_eq!(one.pblock.snapping_mode, SnappingMode::On);
+    assert!(one.pblock.reset_after_reconfig);
+
+    let list: Module = toml::from_str(
+        r#"
+        region = ["CLOCKREGION_X0Y0:CLOCKREGION_X0Y1"]
+        pblock = { snapping_mode = "routing", contain_routing = true }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(list.region.len(), 1);
+    assert_eq!(list.pblock.snapping_mode, SnappingMode::Routing);
+    assert!(list.pblock.contain_routing && list.pblock.reset_after_reconfig);
+}
@@ -0,0 +1,62 @@
+//! `pr_verify` across the routed configurations of a static design.
+//!
+//! The first configuration is the initial checkpoint and every other one is
+//! compared against it. The result is written to `reports/pr_verify.rpt` and
+//! a configuration only passes on an explicit "are compatible" line.
+
+use regex::Regex;
+use std::path::Path;
+
+pub const PR_VERIFY_REPORT: &str = "pr_verify.rpt";
+
+/// Routed checkpoint of configuration `name`.
+pub fn routed_checkpoint(name: &str) -> String {
+    format!("{}_routed.dcp", name)
+}
+
+/// Verdict of one checkpoint pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairResult {
+    pub initial: String,
+    pub additional: String,
+    pub compatible: bool,
+}
+
+/// Every `PR_VERIFY: check points <a> and <b> are [not|in]compatible` line.
+pub fn parse_pr_verify(text: &str) -> Vec<PairResult> {
+    let re =
+        Regex::new(r"PR_VERIFY: [Cc]heck ?points? (\S+) and (\S+) (?:are|is) (not |in)?compatible")
+            .expect("valid pr_verify regex");
+
+    re.captures_iter(text)
+        .map(|c| PairResult {
+            initial: c[1].to_string(),
+            additional: c[2].to_string(),
+            compatible: c.get(3).is_none(),
+        })
+        .collect()
+}
+
+fn same_file(reported: &str, checkpoint: &str) -> bool {
+    Path::new(reported).file_name() == Path::new(checkpoint).file_name()
+}
+
+/// Problems found for `additional` checkpoints in a `pr_verify` report;
+/// empty when each of them is compatible with the initial one.
+pub fn failures(text: &str, additional: &[String]) -> Vec<String> {
+    let pairs = parse_pr_verify(text);
+
+    additional
+        .iter()
+        .filter_map(
+            |dcp| match pairs.iter().find(|p| same_file(&p.additional, dcp)) {
+                Some(p) if p.compatible => None,
+                Some(p) => Some(format!("{} is not compatible with {}", dcp, p.initial)),
+                None => Some(format!("{} was not verified", dcp)),
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod test_pr_verify;
@@ -0,0 +1,29 @@
+use super::*;
+
+const REPORT: &str = "\
+INFO: [Vivado 12-3253] PR_VERIFY: check points /b/main/a_routed.dcp and /b/main/b_routed.dcp are compatible
+ERROR: [Vivado 12-3254] PR_VERIFY: check points /b/main/a_routed.dcp and /b/main/c_routed.dcp are not compatible
+";
+
+#[test]
+fn test_parse_pr_verify() {
+    let pairs = parse_pr_verify(REPORT);
+    assert_eq!(pairs.len(), 2);
+    assert!(pairs[0].compatible);
+    assert_eq!(pairs[1].additional, "/b/main/c_routed.dcp");
+    assert!(!pairs[1].compatible);
+}
+
+#[test]
+fn test_failures() {
+    let additional = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    assert!(failures(REPORT, &additional(&["b_routed.dcp"])).is_empty());
+    assert_eq!(
+        failures(REPORT, &additional(&["c_routed.dcp", "d_routed.dcp"])),
+        [
+            "c_routed.dcp is not compatible with /b/main/a_routed.dcp",
+            "d_routed.dcp was not verified"
+        ]
+    );
+}
@@ -0,0 +1,173 @@
+//! Vivado reports written after synthesis and routing, and the figures
+//! spinhdl pulls out of them.
+//!
+//! Every synth and route run writes `<prefix>_utilization.rpt`,
+//! `<prefix>_timing_summary.rpt`, `<prefix>_power.rpt` and `<prefix>_drc.rpt`
+//! into `build/<design>/reports/`, with `synth` or `route_<rm>` as prefix.
+//! [`summarize`] condenses them into `reports/summary.json`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+pub const REPORTS_DIR: &str = "reports";
+pub const SUMMARY_FILE: &str = "summary.json";
+
+/// Worst slacks from `report_timing_summary`, in ns.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct TimingSummary {
+    pub wns: Option<f64>,
+    pub tns: Option<f64>,
+    pub whs: Option<f64>,
+}
+
+/// Resource usage from `report_utilization`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Utilization {
+    pub lut: Option<f64>,
+    pub ff: Option<f64>,
+    pub bram: Option<f64>,
+    pub dsp: Option<f64>,
+}
+
+/// Headline figures of one run.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ReportSummary {
+    #[serde(flatten)]
+    pub utilization: Utilization,
+    #[serde(flatten)]
+    pub timing: TimingSummary,
+    /// total on-chip power, in W
+    pub power: Option<f64>,
+}
+
+/// Path of report `kind` for `prefix` under `reports_dir`.
+pub fn report_path(reports_dir: &Path, prefix: &str, kind: &str) -> PathBuf {
+    reports_dir.join(format!("{}_{}.rpt", prefix, kind))
+}
+
+/// `Used` column of the first table row whose site type is one of `names`.
+fn used(text: &str, names: &[&str]) -> Option<f64> {
+    text.lines().find_map(|line| {
+        let mut cols = line.split('|').map(str::trim).skip(1);
+        let site = cols.next()?.trim_end_matches('*');
+        if !names.contains(&site) {
+            return None;
+        }
+        cols.next()?.parse().ok()
+    })
+}
+
+pub fn parse_utilization(text: &str) -> Utilization {
+    Utilization {
+        lut: used(text, &["CLB LUTs", "Slice LUTs"]),
+        ff: used(text, &["CLB Registers", "Slice Registers"]),
+        bram: used(text, &["Block RAM Tile"]),
+        dsp: used(text, &["DSPs"]),
+    }
+}
+
+/// Values below the `WNS(ns) ...` header of the design timing summary.
+pub fn parse_timing_summary(text: &str) -> TimingSummary {
+    let mut lines = text.lines();
+    let Some(header) = lines.find(|l| l.contains("WNS(ns)") && l.contains("WHS(ns)")) else {
+        return TimingSummary::default();
+    };
+
+    // column names contain single spaces, columns are separated by several
+    let names: Vec<&str> = header
+        .split("  ")
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let rule = |l: &str| l.chars().all(|c| c == '-' || c.is_whitespace());
+    let Some(values) = lines.find(|l| !rule(l)) else {
+        return TimingSummary::default();
+    };
+    let values: Vec<&str> = values.split_whitespace().collect();
+
+    let column = |name: &str| {
+        let idx = names.iter().position(|n| *n == name)?;
+        values.get(idx)?.parse().ok()
+    };
+
+    TimingSummary {
+        wns: column("WNS(ns)"),
+        tns: column("TNS(ns)"),
+        whs: column("WHS(ns)"),
+    }
+}
+
+pub fn parse_power(text: &str) -> Option<f64> {
+    used(text, &["Total On-Chip Power (W)"])
+}
+
+/// Parse the reports of `prefix`; missing reports leave their fields empty.
+pub fn read_summary(reports_dir: &Path, prefix: &str) -> ReportSummary {
+    let read = |kind| fs::read_to_string(report_path(reports_dir, prefix, kind)).ok();
+
+    ReportSummary {
+        utilization: read("utilization")
+            .map(|t| parse_utilization(&t))
+            .unwrap_or_default(),
+        timing: read("timing_summary")
+            .map(|t| parse_timing_summary(&t))
+            .unwrap_or_default(),
+        power: read("power").and_then(|t| parse_power(&t)),
+    }
+}
+
+/// Summarize every report set in `reports_dir` into `summary.json`, keyed by prefix.
+pub fn summarize(reports_dir: &Path) -> io::Result<BTreeMap<String, ReportSummary>> {
+    let mut summary = BTreeMap::new();
+
+    for entry in fs::read_dir(reports_dir)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if let Some(prefix) = name.strip_suffix("_utilization.rpt") {
+            summary.insert(prefix.to_string(), read_summary(reports_dir, prefix));
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&summary).map_err(io::Error::other)?;
+    fs::write(reports_dir.join(SUMMARY_FILE), json)?;
+    Ok(summary)
+}
+
+/// Load a `summary.json` written by [`summarize`].
+pub fn load_summary(reports_dir: &Path) -> io::Result<BTreeMap<String, ReportSummary>> {
+    let text = fs::read_to_string(reports_dir.join(SUMMARY_FILE))?;
+    serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn cell(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{}", v))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Print one row per (design, run) in a fixed-width table.
+pub fn print_table(rows: &[(String, String, ReportSummary)]) {
+    println!(
+        "{:<16} {:<20} {:>8} {:>8} {:>6} {:>6} {:>9} {:>9} {:>9} {:>9}",
+        "design", "run", "LUT", "FF", "BRAM", "DSP", "WNS", "TNS", "WHS", "Power(W)"
+    );
+    for (design, run, s) in rows {
+        println!(
+            "{:<16} {:<20} {:>8} {:>8} {:>6} {:>6} {:>9} {:>9} {:>9} {:>9}",
+            design,
+            run,
+            cell(s.utilization.lut),
+            cell(s.utilization.ff),
+            cell(s.utilization.bram),
+            cell(s.utilization.dsp),
+            cell(s.timing.wns),
+            cell(s.timing.tns),
+            cell(s.timing.whs),
+            cell(s.power)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_reports;
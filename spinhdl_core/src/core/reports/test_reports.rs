@@ -0,0 +1,49 @@
+use super::*;
+
+const UTILIZATION: &str = "\
++----------------------------+------+-------+------------+-----------+-------+
+|          Site Type         | Used | Fixed | Prohibited | Available | Util% |
++----------------------------+------+-------+------------+-----------+-------+
+| CLB LUTs*                  | 1234 |     0 |          0 |     70560 |  1.75 |
+|   LUT as Logic             | 1200 |     0 |          0 |     70560 |  1.70 |
+| CLB Registers              | 2048 |     0 |          0 |    141120 |  1.45 |
++----------------------------+------+-------+------------+-----------+-------+
+| Block RAM Tile    |  4.5 |     0 |          0 |       216 |  2.08 |
+| DSPs           |    8 |     0 |          0 |       360 |  2.22 |
+";
+
+const TIMING: &str = "\
+| Design Timing Summary
+| ---------------------
+
+    WNS(ns)      TNS(ns)  TNS Failing Endpoints  TNS Total Endpoints      WHS(ns)      THS(ns)
+    -------      -------  ---------------------  -------------------      -------      -------
+     -0.412      -12.075                     37                 4321        0.021        0.000
+";
+
+#[test]
+fn test_parse_utilization() {
+    let u = parse_utilization(UTILIZATION);
+    assert_eq!(u.lut, Some(1234.0));
+    assert_eq!(u.ff, Some(2048.0));
+    assert_eq!(u.bram, Some(4.5));
+    assert_eq!(u.dsp, Some(8.0));
+}
+
+#[test]
+fn test_parse_timing_summary() {
+    let t = parse_timing_summary(TIMING);
+    assert_eq!(t.wns, Some(-0.412));
+    assert_eq!(t.tns, Some(-12.075));
+    assert_eq!(t.whs, Some(0.021));
+    assert_eq!(
+        parse_timing_summary("no timing here"),
+        TimingSummary::default()
+    );
+}
+
+#[test]
+fn test_parse_power() {
+    let text = "| Total On-Chip Power (W)  | 0.712        |\n| Dynamic (W)  | 0.301 |";
+    assert_eq!(parse_power(text), Some(0.712));
+}
@@ -0,0 +1,113 @@
+use crate::flow_graph::BuildStage;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Error, ErrorKind};
+
+/// Marks a retry pattern as a regex rather than literal text.
+const REGEX_PREFIX: &str = "re:";
+
+/// `[project.retry.<stage>]`: how often a failed tool run is retried.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct RetryPolicy {
+    /// extra attempts after the first failure
+    #[serde(default)]
+    pub retries: u32,
+    /// text matched literally, e.g. a message ID such as "[Common 17-345]",
+    /// or a regex prefixed with `re:`; a failure is only retried when its
+    /// output matches one of them
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// `patterns` compiled by [`RetryCfg::compile`]
+    #[serde(skip)]
+    matchers: Vec<Matcher>,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Literal(String),
+    Regex(Regex),
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct RetryCfg {
+    #[serde(default)]
+    pub create_project: RetryPolicy,
+    #[serde(default)]
+    pub synth: RetryPolicy,
+    #[serde(default)]
+    pub route: RetryPolicy,
+    #[serde(default)]
+    pub pr_verify: RetryPolicy,
+    #[serde(default)]
+    pub bitgen: RetryPolicy,
+}
+
+impl RetryCfg {
+    /// Compile the patterns of every stage, failing on the first invalid
+    /// `re:` pattern.
+    pub fn compile(&mut self) -> io::Result<()> {
+        let stages = [
+            ("create_project", &mut self.create_project),
+            ("synth", &mut self.synth),
+            ("route", &mut self.route),
+            ("pr_verify", &mut self.pr_verify),
+            ("bitgen", &mut self.bitgen),
+        ];
+        for (stage, policy) in stages {
+            policy.compile().map_err(|(pattern, e)| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Invalid pattern '{}' in [project.retry.{}]: {}",
+                        pattern, stage, e
+                    ),
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn for_stage(&self, stage: BuildStage) -> &RetryPolicy {
+        match stage {
+            BuildStage::VerifyFiles | BuildStage::CreateProject => &self.create_project,
+            BuildStage::Synth => &self.synth,
+            BuildStage::Route => &self.route,
+            BuildStage::PrVerify => &self.pr_verify,
+            BuildStage::Bitgen => &self.bitgen,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Compile `patterns`, returning the first invalid one with its error.
+    fn compile(&mut self) -> Result<(), (String, regex::Error)> {
+        self.matchers = self
+            .patterns
+            .iter()
+            .map(|p| match p.strip_prefix(REGEX_PREFIX) {
+                Some(re) => Regex::new(re)
+                    .map(Matcher::Regex)
+                    .map_err(|e| (p.clone(), e)),
+                None => Ok(Matcher::Literal(p.clone())),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    /// Whether a failed run that produced `output` should be attempted again.
+    pub fn is_retryable(&self, output: &str) -> bool {
+        self.matchers.iter().any(|m| match m {
+            Matcher::Literal(text) => output.contains(text.as_str()),
+            Matcher::Regex(re) => re.is_match(output),
+        })
+    }
+
+    /// Whether attempt number `attempt` (counting from 1) failing with
+    /// `output` leaves another attempt.
+    pub fn should_retry(&self, attempt: u32, output: &str) -> bool {
+        attempt <= self.retries && self.is_retryable(output)
+    }
+}
+
+#[cfg(test)]
+mod test_retry;
@@ -0,0 +1,76 @@
+use super::*;
+use crate::test_support::{DEMO, load};
+
+fn policy(retries: u32, patterns: &[&str]) -> RetryPolicy {
+    let mut policy = RetryPolicy {
+        retries,
+        patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        ..Default::default()
+    };
+    policy.compile().unwrap();
+    policy
+}
+
+const LICENSE_ERROR: &str =
+    "ERROR: [Common 17-345] A valid license was not found for feature 'Implementation'";
+
+#[test]
+fn test_literal_patterns() {
+    assert!(policy(1, &["Common 17-345"]).is_retryable(LICENSE_ERROR));
+    assert!(policy(1, &["[Common 17-345]"]).is_retryable(LICENSE_ERROR));
+    // a bracketed ID is not a character class matching any 'R', 'o', ...
+    assert!(!policy(1, &["[Route 35-9]"]).is_retryable(LICENSE_ERROR));
+    assert!(!policy(1, &["Common 17-69"]).is_retryable(LICENSE_ERROR));
+    assert!(!policy(1, &[]).is_retryable(LICENSE_ERROR));
+}
+
+#[test]
+fn test_regex_patterns() {
+    assert!(policy(1, &[r"re:Common 17-3\d+"]).is_retryable(LICENSE_ERROR));
+    assert!(!policy(1, &[r"re:^Common"]).is_retryable(LICENSE_ERROR));
+}
+
+#[test]
+fn test_invalid_regex_is_a_config_error() {
+    let mut cfg = RetryCfg::default();
+    cfg.route.patterns = vec!["Common 17-345".to_string(), "re:(unclosed".to_string()];
+    let err = cfg.compile().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(
+        err.to_string()
+            .contains("'re:(unclosed' in [project.retry.route]"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_attempt_limit() {
+    let retry = policy(2, &["Common 17-345"]);
+    assert!(retry.should_retry(1, LICENSE_ERROR));
+    assert!(retry.should_retry(2, LICENSE_ERROR));
+    assert!(!retry.should_retry(3, LICENSE_ERROR));
+    assert!(!retry.should_retry(1, "ERROR: [Synth 8-439] module 'foo' not found"));
+    assert!(!policy(0, &["Common 17-345"]).should_retry(1, LICENSE_ERROR));
+}
+
+#[test]
+fn test_invalid_regex_fails_loading() {
+    use crate::core::BuildCfg;
+    use std::fs;
+
+    let cfg = load("retry_patterns", DEMO);
+    let path = cfg.base_dir.join("spinhdl.toml");
+    let text = fs::read_to_string(&path).unwrap();
+    fs::write(
+        &path,
+        format!(
+            "{}\n[project.retry.synth]\nretries = 1\npatterns = [\"re:[Synth\"]\n",
+            text
+        ),
+    )
+    .unwrap();
+
+    let err = BuildCfg::from_file(&path).unwrap_err();
+    assert!(err.to_string().contains("[project.retry.synth]"), "{}", err);
+}
@@ -0,0 +1,119 @@
+use super::tcl::{TclWriter, quote};
+use std::io::{self, BufRead, BufReader, Error, ErrorKind, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// Printed by the session after every sourced script so we know where its output ends.
+const DONE_MARKER: &str = "__SPINHDL_DONE__";
+/// Prefix of the line carrying the error message of a failed script.
+const ERROR_MARKER: &str = "__SPINHDL_ERROR__";
+
+/// A long-running `vivado -mode tcl` process that scripts are fed to over stdin.
+///
+/// Sourcing a script through a session avoids paying the tool startup cost for
+/// every stage of a design.
+#[derive(Debug)]
+pub struct TclSession {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    seq: u64,
+}
+
+impl TclSession {
+    pub fn spawn(vivado: &Path, work_dir: &Path) -> io::Result<Self> {
+        let mut child = Command::new(vivado)
+            .args(["-nojournal", "-nolog", "-mode", "tcl"])
+            .current_dir(work_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| Error::other("Failed to open Vivado session stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| Error::other("Failed to open Vivado session stdout"))?;
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            seq: 0,
+        })
+    }
+
+    pub fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Source `tcl` from its own directory and wait for it to complete,
+    /// copying the script's output to `log`.
+    ///
+    /// Any project left open by the script is closed afterwards so the next
+    /// script starts from a clean session.
+    pub fn source(&mut self, tcl: &Path, log: &mut impl Write) -> io::Result<()> {
+        let dir = tcl.parent().unwrap_or(Path::new("."));
+        self.seq += 1;
+        let done = format!("{} {}", DONE_MARKER, self.seq);
+
+        let mut script = TclWriter::new();
+        script.cmd("cd").arg(dir.to_string_lossy()).end();
+        script.cmd("source").arg(tcl.to_string_lossy()).end();
+        let script = script.finish();
+        writeln!(
+            self.stdin,
+            "if {{[catch {} err]}} {{puts \"{} [string map {{\\n {{ }}}} $err]\"}}; \
+             catch {{close_project}}; puts \"{}\"; flush stdout",
+            quote(&script),
+            ERROR_MARKER,
+            done
+        )?;
+        self.stdin.flush()?;
+
+        let mut failure = None;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.stdout.read_line(&mut line)? == 0 {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("Vivado session exited while sourcing {}", tcl.display()),
+                ));
+            }
+
+            if line.contains(&done) {
+                break;
+            }
+
+            if let Some(pos) = line.find(ERROR_MARKER) {
+                failure = Some(line[pos + ERROR_MARKER.len()..].trim().to_string());
+            }
+            print!("{}", line);
+            log.write_all(line.as_bytes())?;
+        }
+
+        match failure {
+            Some(msg) => Err(Error::other(format!(
+                "{} failed in Vivado session: {}",
+                tcl.display(),
+                msg
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for TclSession {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, "exit");
+        let _ = self.stdin.flush();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod test_session;
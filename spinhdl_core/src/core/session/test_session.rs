@@ -0,0 +1,38 @@
+use super::*;
+use crate::test_support::{scratch_dir, stub_vivado};
+use std::fs;
+
+#[test]
+#[ignore = "needs tclsh"]
+fn test_session_source() {
+    let dir = scratch_dir("source");
+    let vivado = stub_vivado(&dir);
+    fs::write(
+        dir.join("first.tcl"),
+        "set ::runs 1\nputs \"first [pwd]\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("second.tcl"),
+        "incr ::runs\nputs \"runs $::runs\"\n",
+    )
+    .unwrap();
+    fs::write(dir.join("bad.tcl"), "error \"boom\"\n").unwrap();
+
+    let mut session = TclSession::spawn(&vivado, &dir).unwrap();
+    let mut log = Vec::new();
+    session.source(&dir.join("first.tcl"), &mut log).unwrap();
+    // the same interpreter sees what the first script left behind
+    session.source(&dir.join("second.tcl"), &mut log).unwrap();
+    let err = session.source(&dir.join("bad.tcl"), &mut log).unwrap_err();
+    assert!(err.to_string().contains("boom"));
+    assert!(session.is_alive());
+
+    let log = String::from_utf8(log).unwrap();
+    assert!(log.contains("first "));
+    assert!(log.contains("runs 2"));
+    assert!(!log.contains(DONE_MARKER));
+
+    drop(session);
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,174 @@
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Language / content of a source file, mapped onto Vivado's `FILE_TYPE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileType {
+    Verilog,
+    VerilogHeader,
+    #[serde(rename = "systemverilog")]
+    SystemVerilog,
+    Vhdl,
+    #[serde(rename = "vhdl2008")]
+    Vhdl2008,
+    /// memory initialisation file (`.mem`)
+    Mem,
+    /// coefficient file (`.coe`)
+    Coe,
+}
+
+impl FileType {
+    /// Type Vivado assumes for a file with this extension.
+    pub fn from_path(path: &str) -> Self {
+        let ext = Path::new(path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        match ext.as_str() {
+            "vhd" | "vhdl" => FileType::Vhdl,
+            "sv" | "svh" => FileType::SystemVerilog,
+            "vh" => FileType::VerilogHeader,
+            "mem" => FileType::Mem,
+            "coe" => FileType::Coe,
+            _ => FileType::Verilog,
+        }
+    }
+
+    /// Value of the `FILE_TYPE` property.
+    pub fn vivado_name(self) -> &'static str {
+        match self {
+            FileType::Verilog => "Verilog",
+            FileType::VerilogHeader => "Verilog Header",
+            FileType::SystemVerilog => "SystemVerilog",
+            FileType::Vhdl => "VHDL",
+            FileType::Vhdl2008 => "VHDL 2008",
+            FileType::Mem => "Memory Initialization Files",
+            FileType::Coe => "Coefficient Files",
+        }
+    }
+}
+
+/// Flow stage a source file is used in (`USED_IN`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsedIn {
+    Synthesis,
+    Simulation,
+    Implementation,
+}
+
+/// One `rtl` entry: a bare file name or a table carrying its attributes.
+///
+/// ```toml
+/// rtl = ["top.v", { file = "pkg.vhd", library = "common", file_type = "vhdl2008" }]
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SourceFile {
+    pub file: String,
+    /// VHDL library, `xil_defaultlib` when unset
+    pub library: Option<String>,
+    /// overrides the type derived from the extension
+    pub file_type: Option<FileType>,
+    /// stages the file is used in; every stage when empty
+    #[serde(default)]
+    pub used_in: Vec<UsedIn>,
+    /// absolute path, resolved against `rtl_dir`
+    #[serde(skip_deserializing)]
+    pub path: String,
+    /// `file_type` or the type derived from the extension
+    #[serde(skip_deserializing)]
+    pub kind: Option<FileType>,
+    /// `FILE_TYPE` value of `kind`
+    #[serde(skip_deserializing)]
+    pub vivado_type: String,
+    /// `sim_1` for simulation-only files, `sources_1` otherwise
+    #[serde(skip_deserializing)]
+    pub fileset: String,
+}
+
+impl SourceFile {
+    fn plain(file: String) -> Self {
+        Self {
+            file,
+            library: None,
+            file_type: None,
+            used_in: Vec::new(),
+            path: String::new(),
+            kind: None,
+            vivado_type: String::new(),
+            fileset: String::new(),
+        }
+    }
+
+    pub fn is_sim_only(&self) -> bool {
+        !self.used_in.is_empty() && self.used_in.iter().all(|u| *u == UsedIn::Simulation)
+    }
+
+    /// Fill in the computed fields, `path` relative to `base_dir/dir`.
+    pub fn resolve(&mut self, base_dir: &Path, dir: &str) {
+        self.path = base_dir
+            .join(dir)
+            .join(&self.file)
+            .to_string_lossy()
+            .into_owned();
+
+        let kind = self
+            .file_type
+            .unwrap_or_else(|| FileType::from_path(&self.file));
+        self.kind = Some(kind);
+        self.vivado_type = kind.vivado_name().to_string();
+        self.fileset = if self.is_sim_only() {
+            "sim_1".to_string()
+        } else {
+            "sources_1".to_string()
+        };
+    }
+}
+
+/// `rtl` as either a comma separated string or a list of [`SourceFile`] entries.
+pub fn parse_sources_list<'de, D>(deserializer: D) -> Result<Vec<SourceFile>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Name(String),
+        File(SourceFile),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Sources {
+        Csv(String),
+        List(Vec<Entry>),
+    }
+
+    let files: Vec<SourceFile> = match Sources::deserialize(deserializer)? {
+        Sources::Csv(s) => s
+            .split(',')
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .map(SourceFile::plain)
+            .collect(),
+        Sources::List(entries) => entries
+            .into_iter()
+            .map(|e| match e {
+                Entry::Name(file) => SourceFile::plain(file),
+                Entry::File(file) => file,
+            })
+            .collect(),
+    };
+
+    if files.iter().any(|f| f.file.trim().is_empty()) {
+        return Err(de::Error::custom("empty file name in rtl list"));
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test_sources;
@@ -0,0 +1,47 @@
+use super::*;
+
+#[derive(Deserialize)]
+struct Rtl {
+    #[serde(deserialize_with = "parse_sources_list")]
+    rtl: Vec<SourceFile>,
+}
+
+#[test]
+fn test_comma_separated_rtl() {
+    let cfg: Rtl = toml::from_str(r#"rtl = "top.v, pkg.vhd,""#).unwrap();
+    let files: Vec<_> = cfg.rtl.iter().map(|f| f.file.as_str()).collect();
+    assert_eq!(files, ["top.v", "pkg.vhd"]);
+}
+
+#[test]
+fn test_rtl_entries_with_attributes() {
+    let cfg: Rtl = toml::from_str(
+        r#"rtl = [
+            "top.sv",
+            { file = "pkg.vhd", library = "common", file_type = "vhdl2008" },
+            { file = "tb.sv", used_in = ["simulation"] },
+        ]"#,
+    )
+    .unwrap();
+
+    let mut rtl = cfg.rtl;
+    for src in &mut rtl {
+        src.resolve(Path::new("/proj"), "rtl");
+    }
+
+    assert_eq!(rtl[0].kind, Some(FileType::SystemVerilog));
+    assert_eq!(rtl[0].fileset, "sources_1");
+    assert_eq!(rtl[1].path, "/proj/rtl/pkg.vhd");
+    assert_eq!(rtl[1].library.as_deref(), Some("common"));
+    assert_eq!(rtl[1].vivado_type, "VHDL 2008");
+    assert_eq!(rtl[2].fileset, "sim_1");
+}
+
+#[test]
+fn test_file_type_from_extension() {
+    assert_eq!(FileType::from_path("a.VHDL"), FileType::Vhdl);
+    assert_eq!(FileType::from_path("init.mem"), FileType::Mem);
+    assert_eq!(FileType::from_path("taps.coe"), FileType::Coe);
+    assert_eq!(FileType::from_path("defs.vh"), FileType::VerilogHeader);
+    assert_eq!(FileType::from_path("top.v"), FileType::Verilog);
+}
@@ -0,0 +1,88 @@
+//! Input stamps of the flow nodes.
+//!
+//! After a node runs, its inputs and the artifacts it left behind are written
+//! to `<design>/.stamps/<stage>[_<configuration>].stamp`. The next build skips
+//! the node while the stamp lists the same inputs, every recorded artifact
+//! still exists, the stamp is newer than every `file:` input and it is not
+//! older than the stamps of the nodes it depends on. Reverting a stage
+//! deletes its stamp along with its artifacts.
+
+use crate::flow_graph::{FILE_INPUT, FlowNode};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::{fs, io};
+
+pub const STAMP_DIR: &str = ".stamps";
+
+/// Prefix of the stamp lines naming an artifact the node left behind.
+const OUTPUT_LINE: &str = "output:";
+
+/// Stamp of `node` under `design_dir`, the build directory of its design.
+pub fn stamp_path(design_dir: &Path, node: &FlowNode) -> PathBuf {
+    let name = match &node.configuration {
+        Some(c) => format!("{}_{}.stamp", node.stage.as_str(), c),
+        None => format!("{}.stamp", node.stage.as_str()),
+    };
+    design_dir.join(STAMP_DIR).join(name)
+}
+
+pub fn write_stamp(path: &Path, node: &FlowNode) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut text: String = node.inputs.iter().map(|i| format!("{}\n", i)).collect();
+    // glob artifacts such as logs are only there for reverting
+    for artifact in node
+        .artifacts
+        .iter()
+        .filter(|a| !a.contains('*') && Path::new(a) != path && Path::new(a).exists())
+    {
+        text.push_str(&format!("{}{}\n", OUTPUT_LINE, artifact));
+    }
+    fs::write(path, text)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Whether `node` ran with its current inputs since `deps`, the stamps of the
+/// nodes it depends on, were written. Missing dependency stamps are ignored;
+/// a missing `file:` input or a deleted artifact makes the node stale.
+pub fn is_up_to_date(stamp: &Path, node: &FlowNode, deps: &[PathBuf]) -> bool {
+    let Ok(text) = fs::read_to_string(stamp) else {
+        return false;
+    };
+    let (outputs, inputs): (Vec<&str>, Vec<&str>) =
+        text.lines().partition(|l| l.starts_with(OUTPUT_LINE));
+    if !inputs
+        .into_iter()
+        .eq(node.inputs.iter().map(String::as_str))
+    {
+        return false;
+    }
+    if !outputs
+        .iter()
+        .all(|o| Path::new(&o[OUTPUT_LINE.len()..]).exists())
+    {
+        return false;
+    }
+    let Some(stamped) = modified(stamp) else {
+        return false;
+    };
+
+    let files_older = node
+        .inputs
+        .iter()
+        .filter_map(|i| i.strip_prefix(FILE_INPUT))
+        .all(|f| modified(Path::new(f)).is_some_and(|t| t <= stamped));
+    let deps_older = deps
+        .iter()
+        .filter_map(|d| modified(d))
+        .all(|t| t <= stamped);
+
+    files_older && deps_older
+}
+
+#[cfg(test)]
+mod test_stamps;
@@ -0,0 +1,108 @@
+use super::*;
+use crate::flow_graph::BuildStage;
+use crate::test_support::scratch_dir;
+use std::fs::File;
+use std::time::Duration;
+
+fn set_modified(path: &Path, time: SystemTime) {
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(time)
+        .unwrap();
+}
+
+fn node(inputs: Vec<String>) -> FlowNode {
+    FlowNode {
+        key: "main:route:cfg".to_string(),
+        design: "main".to_string(),
+        stage: BuildStage::Route,
+        configuration: Some("cfg".to_string()),
+        artifacts: Vec::new(),
+        inputs,
+    }
+}
+
+#[test]
+fn test_stamp_path() {
+    let dir = Path::new("/build/main");
+    let mut n = node(Vec::new());
+    assert_eq!(stamp_path(dir, &n), dir.join(".stamps/route_cfg.stamp"));
+    n.configuration = None;
+    assert_eq!(stamp_path(dir, &n), dir.join(".stamps/route.stamp"));
+}
+
+#[test]
+fn test_up_to_date() {
+    let dir = scratch_dir("up_to_date");
+    let src = dir.join("top.v");
+    let dep = dir.join("synth.stamp");
+    fs::write(&src, "module top; endmodule").unwrap();
+    fs::write(&dep, "").unwrap();
+
+    let n = node(vec![
+        "synth.jobs=4".to_string(),
+        format!("{}{}", FILE_INPUT, src.display()),
+    ]);
+    let stamp = stamp_path(&dir, &n);
+    let deps = [dep.clone(), dir.join("missing.stamp")];
+    assert!(!is_up_to_date(&stamp, &n, &deps), "never ran");
+
+    write_stamp(&stamp, &n).unwrap();
+    let now = SystemTime::now();
+    set_modified(&src, now - Duration::from_secs(20));
+    set_modified(&dep, now - Duration::from_secs(10));
+    assert!(is_up_to_date(&stamp, &n, &deps));
+
+    // changed settings
+    let mut changed = n.clone();
+    changed.inputs[0] = "synth.jobs=8".to_string();
+    assert!(!is_up_to_date(&stamp, &changed, &deps));
+
+    // a dependency that ran again, then an edited source
+    set_modified(&dep, now + Duration::from_secs(10));
+    assert!(!is_up_to_date(&stamp, &n, &deps));
+    set_modified(&dep, now - Duration::from_secs(10));
+    set_modified(&src, now + Duration::from_secs(10));
+    assert!(!is_up_to_date(&stamp, &n, &deps));
+
+    fs::remove_file(&src).unwrap();
+    assert!(!is_up_to_date(&stamp, &n, &deps), "missing file input");
+
+    // no inputs at all still needs the stamp
+    let empty = node(Vec::new());
+    write_stamp(&stamp, &empty).unwrap();
+    assert!(is_up_to_date(&stamp, &empty, &[]));
+    assert_eq!(fs::read_to_string(&stamp).unwrap(), "");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_deleted_artifact_is_stale() {
+    let dir = scratch_dir("artifacts");
+    let dcp = dir.join("cfg_routed.dcp");
+    fs::write(&dcp, "dcp").unwrap();
+
+    let mut n = node(vec!["synth.jobs=4".to_string()]);
+    let stamp = stamp_path(&dir, &n);
+    n.artifacts = vec![
+        dcp.display().to_string(),
+        // never written, so not expected either
+        dir.join("cfg.ltx").display().to_string(),
+        dir.join("run_route_cfg.attempt*.log").display().to_string(),
+        stamp.display().to_string(),
+    ];
+    write_stamp(&stamp, &n).unwrap();
+    assert_eq!(
+        fs::read_to_string(&stamp).unwrap(),
+        format!("synth.jobs=4\noutput:{}\n", dcp.display())
+    );
+    assert!(is_up_to_date(&stamp, &n, &[]));
+
+    fs::remove_file(&dcp).unwrap();
+    assert!(!is_up_to_date(&stamp, &n, &[]), "deleted output");
+
+    fs::remove_dir_all(dir).unwrap();
+}
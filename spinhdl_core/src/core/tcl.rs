@@ -0,0 +1,193 @@
+//! Quoting for generated TCL.
+//!
+//! Every value that ends up in a script (paths, cell names, regions, ...) goes
+//! through [`quote`] so that spaces, brackets, `$` or braces in it can't
+//! split a command or run unintended TCL. Scripts are assembled by a
+//! [`TclWriter`], from typed commands or from rendered templates, which reach
+//! the same quoting through the `tcl` and `tcl_args` filters.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::{fs, io};
+
+/// Characters that end or alter a bare TCL word.
+const SPECIAL: &[char] = &[
+    ' ', '\t', '\n', '\r', ';', '{', '}', '[', ']', '$', '\\', '"',
+];
+
+/// Quote `word` so TCL reads it back as exactly one word with its literal value.
+///
+/// Safe words are left bare, words with balanced braces and no backslashes are
+/// brace quoted and everything else is backslash escaped.
+pub fn quote(word: &str) -> String {
+    if word.is_empty() {
+        return "{}".to_string();
+    }
+
+    // a `"` only starts a quoted word at the beginning
+    let bare =
+        !word.starts_with(['"', '#']) && !word.chars().any(|c| c != '"' && SPECIAL.contains(&c));
+    if bare {
+        return word.to_string();
+    }
+
+    if brace_safe(word) {
+        return format!("{{{}}}", word);
+    }
+
+    let mut out = String::with_capacity(word.len() * 2);
+    for c in word.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if SPECIAL.contains(&c) => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Quote `items` as a single word holding a TCL list.
+pub fn quote_list<I, S>(items: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    quote(&args(items))
+}
+
+/// Quote every item of `items` as its own word, space separated.
+pub fn args<I, S>(items: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    items
+        .into_iter()
+        .map(|s| quote(s.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Braces protect everything but unbalanced braces and backslash sequences.
+fn brace_safe(word: &str) -> bool {
+    let mut depth = 0usize;
+    for c in word.chars() {
+        match c {
+            '\\' => return false,
+            '{' => depth += 1,
+            '}' => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+/// Builds a TCL script one command at a time, quoting every argument.
+#[derive(Debug, Default)]
+pub struct TclWriter {
+    script: String,
+}
+
+impl TclWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a command; it is written out by [`Cmd::end`].
+    pub fn cmd(&mut self, name: &str) -> Cmd<'_> {
+        Cmd {
+            line: name.to_string(),
+            writer: self,
+        }
+    }
+
+    /// `name` followed by the quoted `args`, for use as a nested command.
+    pub fn command<I, S>(name: &str, args: I) -> String
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut line = name.to_string();
+        for arg in args {
+            line.push(' ');
+            line.push_str(&quote(arg.as_ref()));
+        }
+        line
+    }
+
+    /// Append `script` as is. Its values must already be quoted, as in a
+    /// template rendered through the `tcl` filters.
+    pub fn script(&mut self, script: &str) {
+        self.script.push_str(script);
+    }
+
+    pub fn finish(self) -> String {
+        self.script
+    }
+
+    /// Write the script out to `path`.
+    pub fn save(self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.script)
+    }
+}
+
+/// A command being written by a [`TclWriter`].
+#[derive(Debug)]
+pub struct Cmd<'a> {
+    writer: &'a mut TclWriter,
+    line: String,
+}
+
+impl Cmd<'_> {
+    /// A literal value, quoted.
+    pub fn arg(mut self, value: impl AsRef<str>) -> Self {
+        self.line.push(' ');
+        self.line.push_str(&quote(value.as_ref()));
+        self
+    }
+
+    /// An option switch such as `-force`, written as is.
+    pub fn flag(mut self, flag: &str) -> Self {
+        self.line.push(' ');
+        self.line.push_str(flag);
+        self
+    }
+
+    /// `flag` followed by its quoted value.
+    pub fn opt(self, flag: &str, value: impl AsRef<str>) -> Self {
+        self.flag(flag).arg(value)
+    }
+
+    /// A single argument holding the TCL list of `items`.
+    pub fn list<I, S>(mut self, items: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.line.push(' ');
+        self.line.push_str(&quote_list(items));
+        self
+    }
+
+    /// The result of a nested command such as [`TclWriter::command`].
+    pub fn subst(mut self, command: &str) -> Self {
+        let _ = write!(self.line, " [{}]", command);
+        self
+    }
+
+    pub fn end(self) {
+        self.writer.script.push_str(&self.line);
+        self.writer.script.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod test_tcl;
@@ -0,0 +1,75 @@
+use super::*;
+
+#[test]
+fn test_plain_words_stay_bare() {
+    assert_eq!(quote("/proj/rtl/top.v"), "/proj/rtl/top.v");
+    assert_eq!(quote("SLICE_X0Y0:SLICE_X10Y10"), "SLICE_X0Y0:SLICE_X10Y10");
+    assert_eq!(quote("NAME=\"x\""), "NAME=\"x\"");
+    assert_eq!(quote(""), "{}");
+}
+
+#[test]
+fn test_hostile_paths_are_braced() {
+    assert_eq!(quote("/my proj/top.v"), "{/my proj/top.v}");
+    assert_eq!(
+        quote("/proj/[exec rm -rf ~]/a.v"),
+        "{/proj/[exec rm -rf ~]/a.v}"
+    );
+    assert_eq!(quote("/proj/$HOME/a.v"), "{/proj/$HOME/a.v}");
+    assert_eq!(quote("/proj/a;b.v"), "{/proj/a;b.v}");
+    assert_eq!(quote("\"quoted\".v"), "{\"quoted\".v}");
+    assert_eq!(quote("/proj/{x}/a.v"), "{/proj/{x}/a.v}");
+}
+
+#[test]
+fn test_unbraceable_paths_are_escaped() {
+    assert_eq!(quote("/proj/a}b.v"), "/proj/a\\}b.v");
+    assert_eq!(quote("/proj/{a.v"), "/proj/\\{a.v");
+    assert_eq!(quote("C:\\my dir\\a.v"), "C:\\\\my\\ dir\\\\a.v");
+    assert_eq!(quote("a}{b [c]"), "a\\}\\{b\\ \\[c\\]");
+    assert_eq!(quote("a\\\n$x"), "a\\\\\\n\\$x");
+}
+
+#[test]
+fn test_lists() {
+    assert_eq!(args(["/a/b.v", "/my dir/c.v"]), "/a/b.v {/my dir/c.v}");
+    assert_eq!(quote_list(["FAST", "WIDTH=32"]), "{FAST WIDTH=32}");
+    assert_eq!(quote_list(["/my dir/inc", "/inc"]), "{{/my dir/inc} /inc}");
+    assert_eq!(quote_list(Vec::<String>::new()), "{}");
+}
+
+#[test]
+fn test_writer() {
+    let mut w = TclWriter::new();
+    w.cmd("add_files")
+        .opt("-fileset", "sources_1")
+        .arg("/my proj/top.v")
+        .end();
+    w.cmd("set_property")
+        .arg("include_dirs")
+        .list(["/inc", "/my $dir"])
+        .subst(&TclWriter::command("get_filesets", ["sources_1"]))
+        .end();
+    w.script("close_design\n");
+    w.cmd("add_cells_to_pblock")
+        .arg("pblock_rp0")
+        .subst(&TclWriter::command("get_cells", ["top/rp[0]"]))
+        .flag("-clear_locs")
+        .end();
+
+    assert_eq!(
+        w.finish(),
+        "add_files -fileset sources_1 {/my proj/top.v}\n\
+         set_property include_dirs {/inc {/my $dir}} [get_filesets sources_1]\n\
+         close_design\n\
+         add_cells_to_pblock pblock_rp0 [get_cells {top/rp[0]}] -clear_locs\n"
+    );
+    assert_eq!(
+        TclWriter::command("source", ["/my proj/run.tcl"]),
+        "source {/my proj/run.tcl}"
+    );
+    assert_eq!(
+        TclWriter::command("close_design", Vec::<String>::new()),
+        "close_design"
+    );
+}
@@ -0,0 +1,254 @@
+//! Named templates behind every generated TCL script.
+//!
+//! Each script is rendered from a built-in template that a project can
+//! override by placing a file with the same name in its templates directory
+//! (`project.templates_dir`, `templates/` by default). `spinhdl templates
+//! export` writes the built-in versions out as a starting point.
+//!
+//! Templates use minijinja syntax and see the following context:
+//!
+//! | template             | context                                                                            |
+//! |----------------------|------------------------------------------------------------------------------------|
+//! | `create_project.tcl` | `project`, `design`                                                                |
+//! | `run_synth.tcl`      | `project`, `design`, `hooks`                                                       |
+//! | `create_pr_xdc.tcl`  | `project`, `design`, `partitions`                                                  |
+//! | `run_route.tcl`      | `project`, `pr`, `design`, `hooks`, `partitions`, `config`, `static_dcp`, `pr_xdc` |
+//! | `pr_verify.tcl`      | `project`, `design`, `initial`, `additional`, `report`                             |
+//! | `run_bitgen.tcl`     | `project`, `pr`, `design`, `hooks`, `partitions`, `config`, `build_id_file`        |
+//! | `zynq_driver.tcl`    | `project`, `vitis_root`, `dir`                                                     |
+//!
+//! With `project.flow = "non_project"` the `*_nonproject.tcl` variants of
+//! `run_synth`, `create_pr_xdc`, `run_route` and `run_bitgen` are used
+//! instead, and `read_sources.tcl` (`project`, `design`) replaces
+//! `create_project.tcl`. `common.tcl` holds macros shared by the others, such
+//! as `reports(prefix)` which writes the reports spinhdl summarizes.
+//!
+//! Every interpolated value should pass through the `tcl` filter, which
+//! quotes it as a single TCL word (sequences become one list word), or
+//! `tcl_args`, which quotes each item of a sequence as its own word.
+//!
+//! - `project`: the `[project]` table.
+//! - `pr`: the `[pr]` table, e.g. `pr.interface` for `write_cfgmem`,
+//!   `pr.stamp` (`usr_access` or `userid`) for the build ID and
+//!   `pr.abstract_shell`.
+//! - `build_id_file`: file holding the build ID of the static design,
+//!   written after routing and stamped into every bitstream.
+//! - `design`: the `[[design]]` entry being built, with the resolved
+//!   `rtl_files` (`sources_1`), `sim_files` (`sim_1`), `xdc_files`,
+//!   `xci_files`, `ip_files` and `include_dir_paths`, the formatted
+//!   `define_list` and `generic_list`, and its `synth` and `impl` options.
+//!   Each `design.rtl` entry carries its `path`, `library`, `file_type`,
+//!   derived `kind` and `vivado_type`, `used_in` and `fileset`.
+//! - `hooks`: hook point (`pre_synth`, `post_route`, ...) -> list of TCL
+//!   files to source there, project hooks first.
+//! - `partitions`: the reconfigurable partitions of the design, each followed
+//!   by the partitions nested in its RMs. Each has `instance_name` (the cell
+//!   path, e.g. `rp0/sub`), `pblock_name`, the pblock `ranges` and `pblock`
+//!   properties (`snapping_mode`, `reset_after_reconfig`, `contain_routing`,
+//!   `exclude_placement`), its `floorplan` XDC when the user supplies the
+//!   pblock, and `rms`, the reconfigurable modules implemented in it. Nested
+//!   partitions also have `parent`, `parent_pblock` and `hosts`, the RMs of
+//!   the parent containing them. `shell_dcp` names the abstract shell of a
+//!   partition.
+//! - `config`: the configuration a route or bitstream script is generated
+//!   for, from `[[pr.configuration]]` or derived from the partitions. It has
+//!   a `name`, `implements_static` (set on the first one) and `modules`, one
+//!   per partition present in it with `instance_name`, `rm`, `pblock`,
+//!   `parent`, `partial_bit`, `partial_bin`, `writes_bin`, set only in
+//!   the first configuration with that `partial_bin`, and `hosts_nested`
+//!   when the RM contains nested partitions. In the `blank` configuration of
+//!   `[pr] blanking` every top-level module is a `greybox` without an `rm`.
+//!   With `[pr] abstract_shell` the configurations after the first have a
+//!   single module and `shell`, the abstract shell they are routed against.
+//!   The first configuration placing a parent RM lists it in
+//!   `subdivisions` (`cell`, `rm`, `subcells`) for `pr_subdivide` and writes
+//!   the parents locked to `locked_dcp`; later ones placing it open that
+//!   checkpoint as `parent_dcp`.
+//! - `static_dcp`: locked static design written by the configuration that
+//!   implements static and opened by the others.
+//! - `pr_xdc`: whether `create_pr_xdc.tcl` ran, i.e. some top-level
+//!   partition has no `floorplan`.
+//! - `initial`, `additional`: routed checkpoint of the first configuration
+//!   and those of the others, compared by `pr_verify` into `reports/<report>`.
+//! - `vitis_root`: install root of the detected Vitis.
+//! - `dir`: directory holding the bitstreams for the Zynq driver flow.
+
+use super::tcl::{self, TclWriter};
+use minijinja::Environment;
+use minijinja::value::{Value, ValueKind};
+use serde::Serialize;
+use std::path::Path;
+use std::{fs, io, io::Error};
+
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("common.tcl", include_str!("../../templates/common.tcl")),
+    (
+        "create_project.tcl",
+        include_str!("../../templates/create_project.tcl"),
+    ),
+    (
+        "run_synth.tcl",
+        include_str!("../../templates/run_synth.tcl"),
+    ),
+    (
+        "create_pr_xdc.tcl",
+        include_str!("../../templates/create_pr_xdc.tcl"),
+    ),
+    (
+        "run_route.tcl",
+        include_str!("../../templates/run_route.tcl"),
+    ),
+    (
+        "run_bitgen.tcl",
+        include_str!("../../templates/run_bitgen.tcl"),
+    ),
+    (
+        "pr_verify.tcl",
+        include_str!("../../templates/pr_verify.tcl"),
+    ),
+    (
+        "read_sources.tcl",
+        include_str!("../../templates/read_sources.tcl"),
+    ),
+    (
+        "run_synth_nonproject.tcl",
+        include_str!("../../templates/run_synth_nonproject.tcl"),
+    ),
+    (
+        "create_pr_xdc_nonproject.tcl",
+        include_str!("../../templates/create_pr_xdc_nonproject.tcl"),
+    ),
+    (
+        "run_route_nonproject.tcl",
+        include_str!("../../templates/run_route_nonproject.tcl"),
+    ),
+    (
+        "run_bitgen_nonproject.tcl",
+        include_str!("../../templates/run_bitgen_nonproject.tcl"),
+    ),
+    (
+        "zynq_driver.tcl",
+        include_str!("../../templates/zynq_driver.tcl"),
+    ),
+];
+
+#[derive(Debug)]
+pub struct Templates {
+    env: Environment<'static>,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl Templates {
+    fn environment() -> Environment<'static> {
+        let mut env = Environment::new();
+        // keep `{% %}` lines out of the generated scripts
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_keep_trailing_newline(true);
+        env.add_filter("tcl", tcl_filter);
+        env.add_filter("tcl_args", tcl_args_filter);
+        env
+    }
+
+    pub fn builtin() -> Self {
+        let mut env = Self::environment();
+        for (name, src) in BUILTIN_TEMPLATES {
+            env.add_template(name, src)
+                .expect("built-in template failed to parse");
+        }
+        Self { env }
+    }
+
+    /// Built-in templates, with any file of the same name in `dir` taking
+    /// precedence.
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        let mut env = Self::environment();
+
+        for (name, src) in BUILTIN_TEMPLATES {
+            let user = dir.join(name);
+            let result = if user.is_file() {
+                println!("Using template override {}", user.display());
+                env.add_template_owned(name.to_string(), fs::read_to_string(&user)?)
+            } else {
+                env.add_template(name, src)
+            };
+
+            result.map_err(|e| template_error(name, e))?;
+        }
+
+        Ok(Self { env })
+    }
+
+    /// Render template `name` with `ctx` into the script `out` is writing.
+    pub fn render<S: Serialize>(&self, name: &str, ctx: S, out: &mut TclWriter) -> io::Result<()> {
+        let tmpl = self
+            .env
+            .get_template(name)
+            .map_err(|e| template_error(name, e))?;
+        let text = tmpl.render(ctx).map_err(|e| template_error(name, e))?;
+        out.script(&text);
+        Ok(())
+    }
+}
+
+/// Write the built-in templates into `dir`, leaving existing files alone
+/// unless `force` is set.
+pub fn export_builtin(dir: &Path, force: bool) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    for (name, src) in BUILTIN_TEMPLATES {
+        let path = dir.join(name);
+        if path.exists() && !force {
+            println!("Skipping existing template {}", path.display());
+            continue;
+        }
+        fs::write(&path, src)?;
+        println!("Exported template {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Items of a sequence, or the value itself, as strings.
+fn words(value: &Value) -> Vec<String> {
+    match value.kind() {
+        ValueKind::Seq => value
+            .try_iter()
+            .map(|items| items.map(|v| word(&v)).collect())
+            .unwrap_or_default(),
+        _ => vec![word(value)],
+    }
+}
+
+fn word(value: &Value) -> String {
+    value
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn tcl_filter(value: Value) -> String {
+    match value.kind() {
+        ValueKind::Seq => tcl::quote_list(words(&value)),
+        _ => tcl::quote(&word(&value)),
+    }
+}
+
+fn tcl_args_filter(value: Value) -> String {
+    tcl::args(words(&value))
+}
+
+fn template_error(name: &str, e: minijinja::Error) -> Error {
+    Error::new(
+        io::ErrorKind::InvalidData,
+        format!("template {}: {:#}", name, e),
+    )
+}
+
+#[cfg(test)]
+mod test_templates;
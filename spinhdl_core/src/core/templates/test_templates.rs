@@ -0,0 +1,210 @@
+use super::*;
+use crate::test_support::{DEMO, load, scratch_dir};
+use minijinja::context;
+
+fn render(templates: &Templates, name: &str) -> String {
+    let mut out = TclWriter::new();
+    templates
+        .render(
+            name,
+            context! { project => context! { name => "demo" }, dir => "bits" },
+            &mut out,
+        )
+        .unwrap();
+    out.finish()
+}
+
+#[test]
+fn test_override_takes_precedence() {
+    let dir = scratch_dir("override");
+    fs::write(dir.join("zynq_driver.tcl"), "custom {{ project.name }}\n").unwrap();
+
+    let templates = Templates::load(&dir).unwrap();
+    assert_eq!(render(&templates, "zynq_driver.tcl"), "custom demo\n");
+    // templates without a file in the override dir stay built in
+    assert!(
+        templates
+            .env
+            .get_template("run_synth.tcl")
+            .unwrap()
+            .source()
+            .contains("synth")
+    );
+
+    let builtin = Templates::load(&dir.join("missing")).unwrap();
+    assert_ne!(render(&builtin, "zynq_driver.tcl"), "custom demo\n");
+
+    fs::write(dir.join("run_route.tcl"), "{% if %}").unwrap();
+    let err = Templates::load(&dir).unwrap_err().to_string();
+    assert!(err.contains("template run_route.tcl"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_export_builtin() {
+    let dir = scratch_dir("export");
+
+    export_builtin(&dir, false).unwrap();
+    for (name, src) in BUILTIN_TEMPLATES {
+        assert_eq!(fs::read_to_string(dir.join(name)).unwrap(), *src);
+    }
+
+    // edits survive a plain export and are replaced by a forced one
+    let edited = dir.join("run_synth.tcl");
+    fs::write(&edited, "edited\n").unwrap();
+    export_builtin(&dir, false).unwrap();
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "edited\n");
+    export_builtin(&dir, true).unwrap();
+    assert_eq!(
+        fs::read_to_string(&edited).unwrap(),
+        include_str!("../../../templates/run_synth.tcl")
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_builtins_render_with_documented_context() {
+    use crate::core::FlowMode;
+
+    for (name, flow) in [
+        ("strict_project", FlowMode::Project),
+        ("strict_nonproject", FlowMode::NonProject),
+    ] {
+        let mut cfg = load(name, &format!("{}\n[pr]\nblanking = true\n", DEMO));
+        cfg.projectcfg.flow = flow;
+        // a key missing from a template's context fails the render
+        cfg.templates
+            .env
+            .set_undefined_behavior(minijinja::UndefinedBehavior::Strict);
+
+        let scripts = cfg.emit_scripts().unwrap();
+        let names: Vec<_> = scripts
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert!(
+            names.contains(&"run_route_logic_1.tcl".to_string()),
+            "{:?}",
+            names
+        );
+        assert!(
+            names.contains(&"run_bitgen_blank.tcl".to_string()),
+            "{:?}",
+            names
+        );
+        assert!(names.contains(&"pr_verify.tcl".to_string()), "{:?}", names);
+    }
+
+    let mut templates = Templates::builtin();
+    templates
+        .env
+        .set_undefined_behavior(minijinja::UndefinedBehavior::Strict);
+    templates
+        .render(
+            "zynq_driver.tcl",
+            context! { project => context! { name => "demo" }, vitis_root => "/opt/Vitis", dir => "bits" },
+            &mut TclWriter::new(),
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_nonproject_scripts_use_checkpoints() {
+    use crate::core::FlowMode;
+
+    let mut cfg = load("nonproject", DEMO);
+    cfg.projectcfg.flow = FlowMode::NonProject;
+    let scripts = cfg.emit_scripts().unwrap();
+    assert!(!scripts.is_empty());
+
+    for script in &scripts {
+        let text = fs::read_to_string(script).unwrap();
+        for cmd in [
+            "open_project",
+            "close_project",
+            "launch_runs",
+            "wait_on_run",
+            "open_run",
+            "get_runs",
+        ] {
+            assert!(
+                !text.contains(cmd),
+                "{} in {}:\n{}",
+                cmd,
+                script.display(),
+                text
+            );
+        }
+    }
+
+    let dir = cfg.design_dir("main");
+    let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+    assert!(read("read_sources.tcl").starts_with("create_project -in_memory"));
+    assert!(read("run_synth.tcl").contains("source read_sources.tcl"));
+    assert!(read("run_synth.tcl").contains("write_checkpoint -force main_synth.dcp"));
+    assert!(read("create_pr_xdc.tcl").contains("open_checkpoint main_synth.dcp"));
+
+    // the static route starts from the PR checkpoint, the others from the
+    // locked static design, each reading its RM by path
+    let first = read("run_route_logic_1.tcl");
+    assert!(first.contains("open_checkpoint main_pr.dcp"), "{}", first);
+    assert!(
+        first.contains("read_checkpoint -cell [get_cells rp0] ../logic_1.dcp"),
+        "{}",
+        first
+    );
+    assert!(
+        first.contains("write_checkpoint -force main_static_routed.dcp"),
+        "{}",
+        first
+    );
+    let second = read("run_route_logic_2.tcl");
+    assert!(
+        second.contains("open_checkpoint main_static_routed.dcp"),
+        "{}",
+        second
+    );
+    assert!(
+        second.contains("read_checkpoint -cell [get_cells rp0] ../logic_2.dcp"),
+        "{}",
+        second
+    );
+    assert!(read("run_bitgen_logic_2.tcl").starts_with("open_checkpoint logic_2_routed.dcp"));
+}
+
+#[test]
+fn test_shell_scripts_skip_project() {
+    let mut cfg = load(
+        "shell_project",
+        &format!("{}\n[pr]\nabstract_shell = true\n", DEMO),
+    );
+    cfg.emit_scripts().unwrap();
+    let dir = cfg.design_dir("main");
+    let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+
+    // the shell routes run in parallel and must not share the project
+    for name in ["run_route_rp0_logic_2.tcl", "run_bitgen_rp0_logic_2.tcl"] {
+        let text = read(name);
+        assert!(
+            !text.contains("open_project") && !text.contains("close_project"),
+            "{}:\n{}",
+            name,
+            text
+        );
+    }
+    assert!(read("run_route_rp0_logic_2.tcl").starts_with("open_checkpoint rp0_shell.dcp\n"));
+    assert!(
+        read("run_bitgen_rp0_logic_2.tcl").starts_with("open_checkpoint rp0_logic_2_routed.dcp\n")
+    );
+    for name in ["run_route_logic_1.tcl", "run_bitgen_logic_1.tcl"] {
+        let text = read(name);
+        assert!(
+            text.starts_with("open_project main.xpr\n") && text.ends_with("close_project\n"),
+            "{}:\n{}",
+            name,
+            text
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use super::*;
+use crate::test_support::{DEMO, load, scratch_dir, stub_vivado};
+
+#[test]
+fn test_paths_resolve_against_config_dir() {
+    let cfg = load(
+        "paths",
+        "design = []\nhier = []\n[root]\ndesign = \"main\"\n",
+    );
+    let base = fs::canonicalize(scratch_dir("paths").join("proj")).unwrap();
+
+    assert_ne!(std::env::current_dir().unwrap(), base);
+    assert_eq!(cfg.base_dir, base);
+    assert_eq!(cfg.build_root(), base.join("build"));
+    assert_eq!(cfg.zynq_driver_dir(None), base.join("build/main"));
+    assert_eq!(cfg.zynq_driver_dir(Some("out/bits")), base.join("out/bits"));
+}
+
+#[test]
+fn test_publish_checkpoint_links_relative() {
+    let dir = scratch_dir("publish");
+    let src = dir.join("logic_1/logic_1/runs/synth_1/logic_1.dcp");
+    fs::create_dir_all(src.parent().unwrap()).unwrap();
+    fs::write(&src, "first").unwrap();
+    let dst = dir.join("logic_1.dcp");
+
+    publish_checkpoint(&src, &dst).unwrap();
+    assert_eq!(
+        fs::read_link(&dst).unwrap(),
+        Path::new("logic_1/logic_1/runs/synth_1/logic_1.dcp")
+    );
+    assert_eq!(fs::read_to_string(&dst).unwrap(), "first");
+
+    // an existing link, or a copy left by an earlier run, is replaced
+    publish_checkpoint(&src, &dst).unwrap();
+    fs::remove_file(&dst).unwrap();
+    fs::write(&dst, "stale").unwrap();
+    fs::write(&src, "second").unwrap();
+    publish_checkpoint(&src, &dst).unwrap();
+    assert!(fs::symlink_metadata(&dst).unwrap().file_type().is_symlink());
+    assert_eq!(fs::read_to_string(&dst).unwrap(), "second");
+
+    let missing = publish_checkpoint(&dir.join("none.dcp"), &dst).unwrap_err();
+    assert_eq!(missing.kind(), ErrorKind::NotFound);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_inputs_on_running_nodes() {
+    let mut cfg = load("inputs", DEMO);
+    cfg.emit_scripts().unwrap();
+    let graph = &cfg.flow_graph;
+    let node = |design, stage, config| graph.get_node(design, stage, config).unwrap();
+
+    let project = &node("main", BuildStage::CreateProject, None).inputs;
+    assert!(project.contains(&"project.part=xczu3eg-sbva484-1-e".to_string()));
+    assert!(
+        project
+            .iter()
+            .any(|i| i.starts_with(FILE_INPUT) && i.ends_with("rtl/top.v")),
+        "{:?}",
+        project
+    );
+
+    let synth = &node("main", BuildStage::Synth, None).inputs;
+    assert!(synth.contains(&"synth.jobs=4".to_string()));
+    assert!(
+        synth.iter().any(|i| i.starts_with("define=FAST")),
+        "{:?}",
+        synth
+    );
+
+    // the design-level route only generates the PR constraints
+    let pr = &node("main", BuildStage::Route, None).inputs;
+    assert!(pr.iter().all(|i| i.starts_with("partition=")), "{:?}", pr);
+
+    let route = &node("main", BuildStage::Route, Some("logic_2")).inputs;
+    assert!(route.contains(&"pr.abstract_shell=false".to_string()));
+    assert!(
+        route
+            .iter()
+            .any(|i| i.starts_with("config={\"name\":\"logic_2\"")),
+        "{:?}",
+        route
+    );
+    assert_ne!(
+        route,
+        &node("main", BuildStage::Route, Some("logic_1")).inputs
+    );
+    let bitgen = &node("main", BuildStage::Bitgen, Some("logic_2")).inputs;
+    assert!(bitgen.contains(&"pr.interface=SMAPx32".to_string()));
+
+    // reverting a stage drops its stamp
+    let stamp = cfg.design_dir("main").join(".stamps/route_logic_2.stamp");
+    assert!(
+        node("main", BuildStage::Route, Some("logic_2"))
+            .artifacts
+            .contains(&stamp.to_string_lossy().into_owned())
+    );
+}
+
+#[test]
+fn test_emit_scripts_per_configuration() {
+    let mut cfg = load("emit", &format!("{}\n[pr]\nblanking = true\n", DEMO));
+    let build_root = cfg.build_root();
+    assert!(build_root.starts_with(std::env::temp_dir()));
+    // nothing may run, so an unusable Vivado must not matter
+    cfg.tools.vivado = None;
+
+    let scripts = cfg.emit_scripts().unwrap();
+    let names: Vec<_> = scripts
+        .iter()
+        .map(|p| {
+            p.strip_prefix(&build_root)
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(
+        sorted,
+        [
+            "logic_1/create_project.tcl",
+            "logic_1/run_synth.tcl",
+            "logic_2/create_project.tcl",
+            "logic_2/run_synth.tcl",
+            "main/create_pr_xdc.tcl",
+            "main/create_project.tcl",
+            "main/pr_verify.tcl",
+            "main/run_bitgen_blank.tcl",
+            "main/run_bitgen_logic_1.tcl",
+            "main/run_bitgen_logic_2.tcl",
+            "main/run_route_blank.tcl",
+            "main/run_route_logic_1.tcl",
+            "main/run_route_logic_2.tcl",
+            "main/run_synth.tcl",
+        ]
+    );
+
+    // flow order: the static route after the PR constraints and before the
+    // other routes, bitstreams after pr_verify
+    let pos = |name: &str| names.iter().position(|n| n == name).unwrap();
+    assert!(pos("main/run_synth.tcl") < pos("main/create_pr_xdc.tcl"));
+    assert!(pos("logic_2/run_synth.tcl") < pos("main/run_route_logic_2.tcl"));
+    assert!(pos("main/create_pr_xdc.tcl") < pos("main/run_route_logic_1.tcl"));
+    assert!(pos("main/run_route_logic_1.tcl") < pos("main/run_route_logic_2.tcl"));
+    assert!(pos("main/run_route_logic_1.tcl") < pos("main/run_route_blank.tcl"));
+    assert!(pos("main/run_route_blank.tcl") < pos("main/pr_verify.tcl"));
+    assert!(pos("main/pr_verify.tcl") < pos("main/run_bitgen_logic_1.tcl"));
+
+    // only scripts were written: no logs, checkpoints or stamps
+    let mut written = Vec::new();
+    let mut pending = vec![build_root.clone()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                written.push(path);
+            }
+        }
+    }
+    written.sort();
+    let mut expected = scripts.clone();
+    expected.sort();
+    assert_eq!(written, expected);
+}
+
+#[test]
+fn test_revert_includes_configuration_nodes() {
+    let mut cfg = load(
+        "revert",
+        &format!("{}\n[pr]\nabstract_shell = true\n", DEMO),
+    );
+    cfg.emit_scripts().unwrap();
+    let dir = cfg.design_dir("main");
+
+    let route = [
+        "build_id.txt",
+        "rp0_shell.dcp",
+        "main_static_routed.dcp",
+        "logic_1_routed.dcp",
+        "rp0_logic_2_routed.dcp",
+        "reports/route_logic_1_timing_summary.rpt",
+        ".stamps/route_rp0_logic_2.stamp",
+    ];
+    let bitgen = ["logic_1.bit", "logic_2_part.bin", manifest::MANIFEST_FILE];
+    for name in route.iter().chain(&bitgen) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "x").unwrap();
+    }
+
+    cfg.revert_stage("main", BuildStage::Route);
+    for name in route
+        .iter()
+        .chain(["run_route_logic_1.tcl", "run_route_rp0_logic_2.tcl"].iter())
+    {
+        assert!(!dir.join(name).exists(), "{} kept", name);
+    }
+    for name in &bitgen {
+        assert!(dir.join(name).exists(), "{} removed", name);
+    }
+    // the synth stage is left alone
+    assert!(dir.join("run_synth.tcl").exists());
+
+    cfg.revert_stage("main", BuildStage::Bitgen);
+    for name in bitgen.iter().chain(["run_bitgen_logic_1.tcl"].iter()) {
+        assert!(!dir.join(name).exists(), "{} kept", name);
+    }
+}
+
+#[test]
+fn test_adding_shell_rm_keeps_static_route() {
+    let body = |rms: &str| {
+        format!(
+            "{}\n[pr]\nabstract_shell = true\n",
+            DEMO.replace("rm = [\"logic_1\", \"logic_2\"]", rms)
+        )
+    };
+    let inputs = |name: &str, rms: &str| {
+        let mut cfg = load(name, &body(rms));
+        cfg.emit_scripts().unwrap();
+        let node = |config| {
+            cfg.flow_graph
+                .get_node("main", BuildStage::Route, config)
+                .unwrap()
+                .inputs
+                .clone()
+        };
+        (node(None), node(Some("logic_1")))
+    };
+
+    // logic_2 is only routed against the abstract shell
+    let (pr, static_route) = inputs("shell_one_rm", "rm = [\"logic_1\"]");
+    assert_eq!(
+        inputs("shell_two_rms", "rm = [\"logic_1\", \"logic_2\"]"),
+        (pr, static_route)
+    );
+
+    let cfg = load("shell_outputs", &body("rm = [\"logic_1\"]"));
+    assert_eq!(
+        cfg.static_outputs("main"),
+        ["main_static_routed.dcp", "rp0_shell.dcp"]
+    );
+}
+
+#[test]
+#[ignore = "needs tclsh"]
+fn test_parallel_scripts_get_their_own_session() {
+    use crate::toolchain::{Tool, ToolInfo};
+
+    let mut cfg = load("sessions", DEMO);
+    cfg.projectcfg.session = true;
+    let dir = cfg.design_dir("main");
+    fs::create_dir_all(&dir).unwrap();
+    let stub = stub_vivado(&dir);
+    cfg.tools.vivado = Some(ToolInfo {
+        tool: Tool::Vivado,
+        exe: stub,
+        root: None,
+        version: None,
+    });
+
+    // each script waits for the other to start, which only happens when
+    // they do not share a session
+    for (me, other) in [("a", "b"), ("b", "a")] {
+        let wait = format!(
+            "close [open {me}.started w]\n\
+             for {{set i 0}} {{![file exists {other}.started]}} {{incr i}} {{\n\
+             if {{$i > 100}} {{ error \"{other} never started\" }}\n\
+             after 50\n}}\n"
+        );
+        fs::write(dir.join(format!("{}.tcl", me)), wait).unwrap();
+    }
+    thread::scope(|s| {
+        for me in ["a", "b"] {
+            let (cfg, dir) = (&cfg, &dir);
+            s.spawn(move || {
+                cfg.run_tcl("main", BuildStage::Route, &dir.join(format!("{}.tcl", me)))
+                    .unwrap()
+            });
+        }
+    });
+    assert_eq!(cfg.sessions.lock().unwrap().len(), 1);
+
+    cfg.close_sessions();
+}
+
+#[test]
+fn test_deleted_output_reruns_stage() {
+    let mut cfg = load("deleted_output", DEMO);
+    cfg.emit_scripts().unwrap();
+    let rtl = cfg.base_dir.join("rtl/rm1.v");
+    fs::create_dir_all(rtl.parent().unwrap()).unwrap();
+    fs::write(&rtl, "module rm; endmodule").unwrap();
+    let dcp = cfg.synth_checkpoint("logic_1");
+    fs::create_dir_all(dcp.parent().unwrap()).unwrap();
+    fs::write(&dcp, "dcp").unwrap();
+
+    cfg.write_stamp("logic_1", BuildStage::Synth, None);
+    assert!(cfg.is_up_to_date("logic_1", BuildStage::Synth, None));
+    fs::remove_file(&dcp).unwrap();
+    assert!(!cfg.is_up_to_date("logic_1", BuildStage::Synth, None));
+}
@@ -0,0 +1,62 @@
+use super::reports::TimingSummary;
+use serde::{Deserialize, Serialize};
+
+/// `[project.timing]`: when a routed design is good enough to bitgen.
+///
+/// Slacks are in ns. Every threshold is the lowest acceptable value and takes
+/// precedence over `fail_on_negative_slack` for its slack.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct TimingCfg {
+    /// fail when WNS, TNS or WHS is negative
+    #[serde(default)]
+    pub fail_on_negative_slack: bool,
+    pub min_wns: Option<f64>,
+    pub min_tns: Option<f64>,
+    pub min_whs: Option<f64>,
+    /// paths listed in the worst path report printed on failure
+    #[serde(default = "default_worst_paths")]
+    pub worst_paths: u32,
+}
+
+fn default_worst_paths() -> u32 {
+    10
+}
+
+impl TimingCfg {
+    pub fn is_enabled(&self) -> bool {
+        self.fail_on_negative_slack
+            || self.min_wns.is_some()
+            || self.min_tns.is_some()
+            || self.min_whs.is_some()
+    }
+
+    /// Lowest acceptable value of a slack given its own threshold.
+    fn limit(&self, threshold: Option<f64>) -> Option<f64> {
+        threshold.or(self.fail_on_negative_slack.then_some(0.0))
+    }
+
+    /// Descriptions of every threshold `timing` violates. A slack that was
+    /// not reported counts as a violation of its threshold.
+    pub fn violations(&self, timing: &TimingSummary) -> Vec<String> {
+        let checks = [
+            ("WNS", timing.wns, self.limit(self.min_wns)),
+            ("TNS", timing.tns, self.limit(self.min_tns)),
+            ("WHS", timing.whs, self.limit(self.min_whs)),
+        ];
+
+        checks
+            .into_iter()
+            .filter_map(|(name, value, limit)| {
+                let limit = limit?;
+                match value {
+                    Some(v) if v >= limit => None,
+                    Some(v) => Some(format!("{} {:.3} ns is below {:.3} ns", name, v, limit)),
+                    None => Some(format!("{} was not reported", name)),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_timing;
@@ -0,0 +1,62 @@
+use super::*;
+
+fn summary(wns: f64, tns: f64, whs: f64) -> TimingSummary {
+    TimingSummary {
+        wns: Some(wns),
+        tns: Some(tns),
+        whs: Some(whs),
+    }
+}
+
+#[test]
+fn test_disabled_gate_accepts_anything() {
+    let cfg = TimingCfg::default();
+    assert!(!cfg.is_enabled());
+    assert!(cfg.violations(&summary(-1.0, -50.0, -0.2)).is_empty());
+}
+
+#[test]
+fn test_fail_on_negative_slack() {
+    let cfg = TimingCfg {
+        fail_on_negative_slack: true,
+        ..Default::default()
+    };
+    assert!(cfg.violations(&summary(0.1, 0.0, 0.02)).is_empty());
+    assert_eq!(cfg.violations(&summary(-0.4, -12.0, 0.02)).len(), 2);
+    assert_eq!(
+        cfg.violations(&TimingSummary::default()),
+        [
+            "WNS was not reported",
+            "TNS was not reported",
+            "WHS was not reported"
+        ]
+    );
+}
+
+#[test]
+fn test_thresholds() {
+    let cfg = TimingCfg {
+        min_wns: Some(-0.5),
+        min_whs: Some(0.05),
+        ..Default::default()
+    };
+    assert!(cfg.violations(&summary(-0.4, -12.0, 0.06)).is_empty());
+    assert_eq!(
+        cfg.violations(&summary(-0.6, -12.0, 0.01)),
+        [
+            "WNS -0.600 ns is below -0.500 ns",
+            "WHS 0.010 ns is below 0.050 ns"
+        ]
+    );
+
+    // a threshold relaxes fail_on_negative_slack for its own slack only
+    let cfg = TimingCfg {
+        fail_on_negative_slack: true,
+        ..cfg
+    };
+    assert!(cfg.violations(&summary(-0.4, 0.0, 0.06)).is_empty());
+    assert_eq!(
+        cfg.violations(&summary(-0.4, -3.0, 0.06)),
+        ["TNS -3.000 ns is below 0.000 ns"]
+    );
+}
@@ -1,3 +1,4 @@
+use crate::core::pblock::{PblockCfg, parse_region};
 use petgraph::Direction;
 use petgraph::graph::{Graph, NodeIndex};
 use petgraph::visit::EdgeRef;
@@ -7,8 +8,15 @@ use std::collections::HashMap;
 #[derive(Debug, Deserialize)]
 pub struct ModuleEntry {
     pub name: String,
+    /// pblock ranges of the partition
+    #[serde(default, deserialize_with = "parse_region")]
+    pub region: Vec<String>,
     #[serde(default)]
-    pub region: Option<String>,
+    pub pblock: PblockCfg,
+    /// existing XDC holding the pblock and `HD.RECONFIGURABLE` of the
+    /// partition, used instead of generating them
+    #[serde(default)]
+    pub floorplan: Option<String>,
     #[serde(default)]
     pub rm: Vec<String>,
 }
@@ -4,14 +4,13 @@ use petgraph::visit::EdgeRef;
 use petgraph::{Direction, algo};
 use std::collections::HashMap;
 
-use crate::core::{BuildCfg, BuildTasks};
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BuildStage {
     VerifyFiles,
     CreateProject,
     Synth,
     Route,
+    PrVerify,
     Bitgen,
 }
 
@@ -22,16 +21,19 @@ impl BuildStage {
             BuildStage::CreateProject => "create_project",
             BuildStage::Synth => "synth",
             BuildStage::Route => "route",
+            BuildStage::PrVerify => "pr_verify",
             BuildStage::Bitgen => "bitgen",
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "verify_files" => Some(BuildStage::VerifyFiles),
             "create_project" => Some(BuildStage::CreateProject),
             "synth" => Some(BuildStage::Synth),
             "route" => Some(BuildStage::Route),
+            "pr_verify" => Some(BuildStage::PrVerify),
             "bitgen" => Some(BuildStage::Bitgen),
             _ => None,
         }
@@ -41,13 +43,21 @@ impl BuildStage {
 /// One node == one *stage* of a specific design (e.g., main:route)
 #[derive(Debug, Clone)]
 pub struct FlowNode {
-    /// stable key like "main:synth"
+    /// stable key like "main:synth", or "main:route:cfg_a" for a configuration
     pub key: String,
     pub design: String,
     pub stage: BuildStage,
+    /// DFX configuration the stage is run for
+    pub configuration: Option<String>,
     pub artifacts: Vec<String>,
+    /// settings that affect the stage's outputs (e.g. "synth.jobs=8"), and
+    /// `file:<path>` for every file the stage reads
+    pub inputs: Vec<String>,
 }
 
+/// Prefix of an input naming a file rather than a setting.
+pub const FILE_INPUT: &str = "file:";
+
 /// Edges represent ordering/dependencies between stages
 #[derive(Debug, Clone, Copy)]
 pub enum FlowEdge {
@@ -74,8 +84,26 @@ impl FlowGraph {
         format!("{}:{}", design, stage.as_str())
     }
 
+    fn config_key(design: &str, stage: BuildStage, configuration: Option<&str>) -> String {
+        match configuration {
+            Some(c) => format!("{}:{}", Self::key(design, stage), c),
+            None => Self::key(design, stage),
+        }
+    }
+
     pub fn ensure_node(&mut self, design: &str, stage: BuildStage) -> NodeIndex {
-        let k = Self::key(design, stage);
+        self.ensure_config_node(design, stage, None)
+    }
+
+    /// Node of `stage` of `design` for one DFX configuration, or the design
+    /// node itself when `configuration` is `None`.
+    pub fn ensure_config_node(
+        &mut self,
+        design: &str,
+        stage: BuildStage,
+        configuration: Option<&str>,
+    ) -> NodeIndex {
+        let k = Self::config_key(design, stage, configuration);
         if let Some(&idx) = self.index.get(&k) {
             return idx;
         }
@@ -83,7 +111,9 @@ impl FlowGraph {
             key: k.clone(),
             design: design.to_string(),
             stage,
+            configuration: configuration.map(str::to_string),
             artifacts: Vec::new(),
+            inputs: Vec::new(),
         });
         self.index.insert(k, idx);
         idx
@@ -95,6 +125,10 @@ impl FlowGraph {
         self.graph.add_edge(u, v, FlowEdge::Depends);
     }
 
+    pub fn depend_nodes(&mut self, before: NodeIndex, after: NodeIndex) {
+        self.graph.add_edge(before, after, FlowEdge::Depends);
+    }
+
     pub fn to_dot(&self) -> String {
         format!(
             "{:?}",
@@ -141,12 +175,61 @@ impl FlowGraph {
         }
     }
 
+    pub fn add_input(&mut self, design: &str, stage: BuildStage, input: &str) {
+        let key = Self::key(design, stage);
+        if let Some(&idx) = self.index.get(&key) {
+            self.graph[idx].inputs.push(input.to_string());
+        }
+    }
+
+    pub fn get_inputs(&self, design: &str, stage: BuildStage) -> Option<&[String]> {
+        self.index
+            .get(&Self::key(design, stage))
+            .map(|&idx| self.graph[idx].inputs.as_slice())
+    }
+
+    pub fn get_node(
+        &self,
+        design: &str,
+        stage: BuildStage,
+        configuration: Option<&str>,
+    ) -> Option<&FlowNode> {
+        self.index
+            .get(&Self::config_key(design, stage, configuration))
+            .map(|&idx| &self.graph[idx])
+    }
+
+    /// Nodes `node` directly depends on.
+    pub fn dependencies(&self, node: &FlowNode) -> Vec<&FlowNode> {
+        let Some(&idx) = self.index.get(&node.key) else {
+            return Vec::new();
+        };
+        self.graph
+            .neighbors_directed(idx, Direction::Incoming)
+            .map(|i| &self.graph[i])
+            .collect()
+    }
+
     pub fn get_artifacts(&self, design: &str, stage: BuildStage) -> Option<&[String]> {
         self.index
             .get(&Self::key(design, stage))
             .map(|&idx| self.graph[idx].artifacts.as_slice())
     }
 
+    /// Artifacts of `design:stage` and of every configuration node of that
+    /// stage, or `None` when the design has no such stage.
+    pub fn stage_artifacts(&self, design: &str, stage: BuildStage) -> Option<Vec<String>> {
+        let nodes: Vec<&FlowNode> = self
+            .graph
+            .node_weights()
+            .filter(|n| n.design == design && n.stage == stage)
+            .collect();
+        if nodes.is_empty() {
+            return None;
+        }
+        Some(nodes.into_iter().flat_map(|n| n.artifacts.clone()).collect())
+    }
+
     pub fn all_artifacts(&self) -> Vec<String> {
         self.graph
             .node_weights()
@@ -155,11 +238,16 @@ impl FlowGraph {
     }
 
     pub fn topo_order(&self) -> Vec<String> {
-        let order = algo::toposort(&self.graph, None)
-            .expect("Cycle in flow graph (unexpected for a build plan)");
-        order
+        self.nodes_in_order()
             .into_iter()
-            .map(|i| self.graph[i].key.clone())
+            .map(|n| n.key.clone())
             .collect()
     }
+
+    /// Every node, each after the nodes it depends on.
+    pub fn nodes_in_order(&self) -> Vec<&FlowNode> {
+        let order = algo::toposort(&self.graph, None)
+            .expect("Cycle in flow graph (unexpected for a build plan)");
+        order.into_iter().map(|i| &self.graph[i]).collect()
+    }
 }
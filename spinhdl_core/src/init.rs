@@ -1,13 +1,26 @@
-use crate::core::{BuildTasks, ModuleType};
-use serde::{Deserialize, Deserializer};
+use crate::core::options::{ImplOptions, SynthOptions};
+use crate::core::sources::{SourceFile, parse_sources_list};
+use crate::core::{BuildTasks, ModuleType, hooks::HooksCfg};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
 
-#[derive(Debug, Deserialize)]
+/// Value of a Verilog define or top-level generic.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum HdlValue {
+    Bool(bool),
+    Int(i64),
+    Str(String),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DesignCfg {
     pub name: String,
     pub top: String,
     pub rtl_dir: String,
-    #[serde(deserialize_with = "parse_files_list")]
-    pub rtl: Vec<String>,
+    #[serde(deserialize_with = "parse_sources_list")]
+    pub rtl: Vec<SourceFile>,
     pub xdc_dir: String,
     #[serde(deserialize_with = "parse_files_list")]
     pub xdc: Vec<String>,
@@ -19,37 +32,93 @@ pub struct DesignCfg {
     pub ip: Vec<String>,
     pub build: BuildTasks,
     pub moduletype: ModuleType,
-    #[serde(skip)]
+    #[serde(default)]
+    pub hooks: HooksCfg,
+    #[serde(default)]
+    pub synth: SynthOptions,
+    #[serde(default, rename = "impl")]
+    pub implementation: ImplOptions,
+    /// Verilog defines; `true` defines the bare macro, `false` leaves it out
+    #[serde(default)]
+    pub defines: BTreeMap<String, HdlValue>,
+    /// top-level VHDL generics / Verilog parameters
+    #[serde(default)]
+    pub generics: BTreeMap<String, HdlValue>,
+    /// include search paths, relative to the config file
+    #[serde(default)]
+    pub include_dirs: Vec<String>,
+    /// `rtl` entries added to `sources_1`
+    #[serde(skip_deserializing)]
     pub rtl_files: Vec<String>,
-    #[serde(skip)]
+    /// simulation-only `rtl` entries, added to `sim_1`
+    #[serde(skip_deserializing)]
+    pub sim_files: Vec<String>,
+    #[serde(skip_deserializing)]
     pub xdc_files: Vec<String>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub xci_files: Vec<String>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub ip_files: Vec<String>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
+    pub include_dir_paths: Vec<String>,
+    /// `defines` as `verilog_define` entries
+    #[serde(skip_deserializing)]
+    pub define_list: Vec<String>,
+    /// `generics` as `generic` entries
+    #[serde(skip_deserializing)]
+    pub generic_list: Vec<String>,
+    #[serde(skip_deserializing)]
     pub build_path: String,
 }
 
 impl DesignCfg {
-    pub fn populate_files(&mut self) {
-        self.rtl_files = populate_files_list(&self.rtl_dir, &self.rtl);
-        self.xdc_files = populate_files_list(&self.xdc_dir, &self.xdc);
-        self.xci_files = populate_files_list(&self.xci_dir, &self.xci);
-        self.ip_files = populate_files_list(&self.ip_dir, &self.ip);
+    /// Resolve every source list against `base_dir` (the config file's directory).
+    pub fn populate_files(&mut self, base_dir: &Path) {
+        for src in &mut self.rtl {
+            src.resolve(base_dir, &self.rtl_dir);
+        }
+        let (sim, rtl): (Vec<_>, Vec<_>) = self.rtl.iter().partition(|s| s.is_sim_only());
+        self.rtl_files = rtl.into_iter().map(|s| s.path.clone()).collect();
+        self.sim_files = sim.into_iter().map(|s| s.path.clone()).collect();
+        self.xdc_files = populate_files_list(base_dir, &self.xdc_dir, &self.xdc);
+        self.xci_files = populate_files_list(base_dir, &self.xci_dir, &self.xci);
+        self.ip_files = populate_files_list(base_dir, &self.ip_dir, &self.ip);
+        self.include_dir_paths = populate_files_list(base_dir, "", &self.include_dirs);
+
+        self.define_list = self
+            .defines
+            .iter()
+            .filter_map(|(name, value)| match value {
+                HdlValue::Bool(true) => Some(name.clone()),
+                HdlValue::Bool(false) => None,
+                HdlValue::Int(v) => Some(format!("{}={}", name, v)),
+                HdlValue::Str(v) => Some(format!("{}={}", name, v)),
+            })
+            .collect();
+
+        // booleans use Verilog literals, which Vivado also maps onto VHDL booleans
+        self.generic_list = self
+            .generics
+            .iter()
+            .map(|(name, value)| match value {
+                HdlValue::Bool(v) => format!("{}=1'b{}", name, u8::from(*v)),
+                HdlValue::Int(v) => format!("{}={}", name, v),
+                HdlValue::Str(v) => format!("{}=\"{}\"", name, v),
+            })
+            .collect();
     }
 
-    pub fn verify_files_exist(&mut self) {
-        self.populate_files();
+    pub fn verify_files_exist(&mut self, base_dir: &Path) {
+        self.populate_files(base_dir);
 
-        for file in &self.rtl_files {
+        for file in self.rtl.iter().map(|s| &s.path) {
             if !std::path::Path::new(file).exists() {
                 println!("Missing RTL file: {}", file);
                 panic!("Files missing");
             }
         }
 
-        if self.xdc_files.len() > 0 {
+        if !self.xdc_files.is_empty() {
             for file in &self.xdc_files {
                 if !std::path::Path::new(file).exists() {
                     println!("Missing XDC file: {}", file);
@@ -58,7 +127,7 @@ impl DesignCfg {
             }
         }
 
-        if self.xci_files.len() > 0 {
+        if !self.xci_files.is_empty() {
             for file in &self.xci_files {
                 if !std::path::Path::new(file).exists() {
                     println!("Missing XCI file: {}", file);
@@ -67,7 +136,14 @@ impl DesignCfg {
             }
         }
 
-        if self.ip_files.len() > 0 {
+        for dir in &self.include_dir_paths {
+            if !std::path::Path::new(dir).is_dir() {
+                println!("Missing include directory: {}", dir);
+                panic!("Files missing");
+            }
+        }
+
+        if !self.ip_files.is_empty() {
             for file in &self.ip_files {
                 if !std::path::Path::new(file).exists() {
                     println!("Missing IP file: {}", file);
@@ -75,6 +151,10 @@ impl DesignCfg {
                 }
             }
         }
+        if let Some(hook) = self.hooks.missing().first() {
+            println!("Missing hook file: {}", hook);
+            panic!("Files missing");
+        }
         println!("All RTL files exist for '{}'", self.name);
     }
 }
@@ -90,9 +170,12 @@ where
         .collect())
 }
 
-fn populate_files_list(dir: &str, files: &Vec<String>) -> Vec<String> {
+fn populate_files_list(base_dir: &Path, dir: &str, files: &[String]) -> Vec<String> {
     files
         .iter()
-        .map(|f| format!("{}/{}", dir.trim_end_matches('/'), f))
+        .map(|f| base_dir.join(dir).join(f).to_string_lossy().into_owned())
         .collect()
 }
+
+#[cfg(test)]
+mod test_init;
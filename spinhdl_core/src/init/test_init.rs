@@ -0,0 +1,90 @@
+use crate::core::FlowMode;
+use crate::test_support::{DEMO, load};
+use std::fs;
+
+const VALUES: &str = r#"defines = { SIM = false, FAST = true, WIDTH = 32, NAME = "my name" }
+generics = { EN = true, OFF = false, DEPTH = 16, MODE = "fast" }
+include_dirs = ["inc", "common/inc"]
+"#;
+
+fn demo_with_values() -> String {
+    DEMO.replacen("defines = { FAST = true }\n", VALUES, 1)
+}
+
+#[test]
+fn test_hdl_values_formatted() {
+    let mut cfg = load("hdl_values", &demo_with_values());
+    let base = cfg.base_dir.clone();
+    let design = cfg.designcfg.iter_mut().find(|d| d.name == "main").unwrap();
+    design.populate_files(&base);
+
+    // false defines are left out, the others keep their value
+    assert_eq!(design.define_list, vec!["FAST", "NAME=my name", "WIDTH=32"]);
+    assert_eq!(
+        design.generic_list,
+        vec!["DEPTH=16", "EN=1'b1", "MODE=\"fast\"", "OFF=1'b0"]
+    );
+    assert_eq!(
+        design.include_dir_paths,
+        vec![
+            base.join("inc").to_string_lossy().into_owned(),
+            base.join("common/inc").to_string_lossy().into_owned(),
+        ]
+    );
+}
+
+#[test]
+fn test_hdl_values_rendered() {
+    for (name, flow) in [
+        ("hdl_project", FlowMode::Project),
+        ("hdl_nonproject", FlowMode::NonProject),
+    ] {
+        let mut cfg = load(name, &demo_with_values());
+        cfg.projectcfg.flow = flow;
+        cfg.emit_scripts().unwrap();
+        let dir = cfg.design_dir("main");
+        let inc = cfg.base_dir.join("inc").to_string_lossy().into_owned();
+
+        let text = match flow {
+            FlowMode::Project => fs::read_to_string(dir.join("create_project.tcl")).unwrap(),
+            FlowMode::NonProject => fs::read_to_string(dir.join("run_synth.tcl")).unwrap(),
+        };
+        assert!(!text.contains("SIM"), "{}", text);
+        match flow {
+            FlowMode::Project => {
+                assert!(
+                    text.contains("set_property verilog_define {FAST {NAME=my name} WIDTH=32} [current_fileset]"),
+                    "{}",
+                    text
+                );
+                assert!(
+                    text.contains("set_property generic {DEPTH=16 EN=1'b1 MODE=\"fast\" OFF=1'b0} [current_fileset]"),
+                    "{}",
+                    text
+                );
+                assert!(
+                    text.contains(&format!("set_property include_dirs {{{} ", inc)),
+                    "{}",
+                    text
+                );
+            }
+            FlowMode::NonProject => {
+                assert!(
+                    text.contains("-verilog_define FAST -verilog_define {NAME=my name} -verilog_define WIDTH=32"),
+                    "{}",
+                    text
+                );
+                assert!(
+                    text.contains("-generic DEPTH=16 -generic EN=1'b1 -generic MODE=\"fast\" -generic OFF=1'b0"),
+                    "{}",
+                    text
+                );
+                assert!(
+                    text.contains(&format!("-include_dirs {{{} ", inc)),
+                    "{}",
+                    text
+                );
+            }
+        }
+    }
+}
@@ -2,8 +2,13 @@ pub mod core;
 pub mod design_hier;
 pub mod init;
 pub mod flow_graph;
+pub mod toolchain;
 
 pub use core::{BuildCfg, ProjectCfg};
 pub use design_hier::{DesignEntry, HierarchyGraph};
 pub use init::DesignCfg;
 pub use flow_graph::*;
+pub use toolchain::{ToolRegistry, ToolchainCfg};
+
+#[cfg(test)]
+pub(crate) mod test_support;
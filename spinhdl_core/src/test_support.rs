@@ -0,0 +1,135 @@
+//! Fixtures shared by the unit tests.
+
+use crate::core::BuildCfg;
+use std::fs;
+use std::ops::{Deref, DerefMut};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Fresh directory `name` under the system temp dir, unique to this test run.
+pub(crate) fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("spinhdl_test_{}_{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+pub(crate) const PROJECT: &str = r#"
+[project]
+name = "demo"
+version = "0.1"
+part = "xczu3eg-sbva484-1-e"
+arch = "zynqmp"
+part_xdc = "xdc/part.xdc"
+build_dir = "build"
+"#;
+
+/// Static `main` with partition `rp0` implementing `logic_1` or `logic_2`.
+pub(crate) const DEMO: &str = r#"
+[root]
+design = "main"
+
+[[design]]
+name = "main"
+top = "top"
+rtl_dir = "rtl"
+rtl = "top.v"
+xdc_dir = "xdc"
+xdc = "main.xdc"
+xci_dir = ""
+xci = ""
+ip_dir = ""
+ip = ""
+build = "bitgen"
+moduletype = "static"
+defines = { FAST = true }
+
+[[design]]
+name = "logic_1"
+top = "rm"
+rtl_dir = "rtl"
+rtl = "rm1.v"
+xdc_dir = ""
+xdc = ""
+xci_dir = ""
+xci = ""
+ip_dir = ""
+ip = ""
+build = "synth"
+moduletype = "recon"
+
+[[design]]
+name = "logic_2"
+top = "rm"
+rtl_dir = "rtl"
+rtl = "rm2.v"
+xdc_dir = ""
+xdc = ""
+xci_dir = ""
+xci = ""
+ip_dir = ""
+ip = ""
+build = "synth"
+moduletype = "recon"
+
+[[hier]]
+name = "main"
+[[hier.modules]]
+name = "rp0"
+region = "SLICE_X0Y0:SLICE_X10Y10"
+rm = ["logic_1", "logic_2"]
+"#;
+
+/// A project loaded by [`load`]; its scratch dir is removed when dropped.
+pub(crate) struct TestProject {
+    cfg: BuildCfg,
+    dir: PathBuf,
+}
+
+impl Deref for TestProject {
+    type Target = BuildCfg;
+
+    fn deref(&self) -> &BuildCfg {
+        &self.cfg
+    }
+}
+
+impl DerefMut for TestProject {
+    fn deref_mut(&mut self) -> &mut BuildCfg {
+        &mut self.cfg
+    }
+}
+
+impl Drop for TestProject {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Write `spinhdl.toml` with `body` followed by the `[project]` table into
+/// scratch dir `name` and load it.
+pub(crate) fn load(name: &str, body: &str) -> TestProject {
+    let dir = scratch_dir(name);
+    let proj = dir.join("proj");
+    fs::create_dir_all(&proj).unwrap();
+    fs::write(proj.join("spinhdl.toml"), format!("{}{}", body, PROJECT)).unwrap();
+    TestProject {
+        cfg: BuildCfg::from_file(&proj.join("spinhdl.toml")).unwrap(),
+        dir,
+    }
+}
+
+/// Stand-in for `vivado -mode tcl` in `dir`: a plain tclsh reading stdin
+/// through a pipe. Tests using it are `#[ignore]`d, as tclsh may be missing.
+pub(crate) fn stub_vivado(dir: &Path) -> PathBuf {
+    let found = Command::new("sh")
+        .args(["-c", "command -v tclsh"])
+        .output()
+        .is_ok_and(|o| o.status.success());
+    assert!(found, "tclsh not found on PATH");
+
+    let stub = dir.join("vivado");
+    fs::write(&stub, "#!/bin/sh\nexec tclsh\n").unwrap();
+    fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+    stub
+}
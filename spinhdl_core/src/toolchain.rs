@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs, io, io::Error};
+
+/// `[project.toolchain]`: pinned tool versions and optional install locations.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ToolchainCfg {
+    /// Required Vivado version, e.g. "2023.2"
+    pub vivado: Option<String>,
+    /// Required Vitis/XSCT version, e.g. "2023.2"
+    pub vitis: Option<String>,
+    /// Path to Vivado's settings64.sh; its directory is the install root
+    pub vivado_settings: Option<String>,
+    /// Path to Vitis' settings64.sh; its directory is the install root
+    pub vitis_settings: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Vivado,
+    Vitis,
+}
+
+impl Tool {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Tool::Vivado => "vivado",
+            Tool::Vitis => "vitis",
+        }
+    }
+
+    /// Executable spinhdl invokes for this tool.
+    fn exe_name(self) -> &'static str {
+        match self {
+            Tool::Vivado => "vivado",
+            Tool::Vitis => "xsct",
+        }
+    }
+}
+
+/// A detected tool installation.
+#[derive(Debug, Clone)]
+pub struct ToolInfo {
+    pub tool: Tool,
+    pub exe: PathBuf,
+    /// install root, e.g. /tools/Xilinx/Vivado/2023.2
+    pub root: Option<PathBuf>,
+    pub version: Option<String>,
+}
+
+/// Every tool of the flow, detected once and shared by all stages.
+#[derive(Debug, Default)]
+pub struct ToolRegistry {
+    pub vivado: Option<ToolInfo>,
+    pub vitis: Option<ToolInfo>,
+}
+
+impl ToolRegistry {
+    pub fn detect(cfg: &ToolchainCfg, base_dir: &Path) -> Self {
+        Self {
+            vivado: detect_tool(Tool::Vivado, cfg.vivado_settings.as_deref(), base_dir),
+            vitis: detect_tool(Tool::Vitis, cfg.vitis_settings.as_deref(), base_dir),
+        }
+    }
+
+    pub fn get(&self, tool: Tool) -> Option<&ToolInfo> {
+        match tool {
+            Tool::Vivado => self.vivado.as_ref(),
+            Tool::Vitis => self.vitis.as_ref(),
+        }
+    }
+
+    /// Executable to run for `tool`, falling back to a plain `PATH` lookup.
+    pub fn exe(&self, tool: Tool) -> PathBuf {
+        self.get(tool)
+            .map(|t| t.exe.clone())
+            .unwrap_or_else(|| PathBuf::from(tool.exe_name()))
+    }
+
+    /// Compare detected versions against the pinned ones in `cfg`.
+    pub fn check_versions(&self, cfg: &ToolchainCfg) -> io::Result<()> {
+        let pinned = [(Tool::Vivado, &cfg.vivado), (Tool::Vitis, &cfg.vitis)];
+        let mut mismatches = Vec::new();
+
+        for (tool, want) in pinned {
+            let Some(want) = want else { continue };
+            let found = self.get(tool).and_then(|t| t.version.as_deref());
+            if !found.is_some_and(|v| version_matches(v, want)) {
+                mismatches.push(format!(
+                    "{} {} required, found {}",
+                    tool.as_str(),
+                    want,
+                    found.unwrap_or("none")
+                ));
+            }
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::other(mismatches.join("; ")))
+        }
+    }
+
+    pub fn write_build_info(&self, path: &Path) -> io::Result<()> {
+        let mut toolchain = BTreeMap::new();
+        for info in [&self.vivado, &self.vitis].into_iter().flatten() {
+            let name = info.tool.as_str();
+            toolchain.insert(
+                format!("{}_version", name),
+                info.version
+                    .clone()
+                    .unwrap_or_else(|| "unknown".to_string()),
+            );
+            toolchain.insert(format!("{}_exe", name), info.exe.display().to_string());
+        }
+
+        let info = BTreeMap::from([("toolchain", toolchain)]);
+        let text = toml::to_string(&info).map_err(Error::other)?;
+        fs::write(path, text)
+    }
+}
+
+/// Whether a detected `found` version satisfies the pinned `want`: equal, or
+/// a patch release of it ("2023.2.1" satisfies "2023.2").
+fn version_matches(found: &str, want: &str) -> bool {
+    found == want
+        || found
+            .strip_prefix(want)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+fn detect_tool(tool: Tool, settings: Option<&str>, base_dir: &Path) -> Option<ToolInfo> {
+    let (exe, root) = match settings {
+        Some(settings) => {
+            let root = base_dir.join(settings).parent()?.to_path_buf();
+            (root.join("bin").join(tool.exe_name()), Some(root))
+        }
+        None => {
+            let exe = find_in_path(tool.exe_name())?;
+            // <root>/bin/<exe>
+            let root = exe.parent().and_then(Path::parent).map(Path::to_path_buf);
+            (exe, root)
+        }
+    };
+
+    if !exe.exists() {
+        return None;
+    }
+
+    let version = match tool {
+        Tool::Vivado => query_version(&exe),
+        Tool::Vitis => None,
+    }
+    .or_else(|| {
+        root.as_deref()
+            .and_then(|r| parse_version(&r.to_string_lossy()))
+    });
+
+    Some(ToolInfo {
+        tool,
+        exe,
+        root,
+        version,
+    })
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|p| p.is_file())
+}
+
+fn query_version(exe: &Path) -> Option<String> {
+    let out = Command::new(exe).arg("-version").output().ok()?;
+    parse_version(&String::from_utf8_lossy(&out.stdout))
+}
+
+/// Find the first Xilinx-style release number (`2023.2`, or `2023.2.1` with a
+/// patch) in `text`.
+pub fn parse_version(text: &str) -> Option<String> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|tok| tok.trim_end_matches('.'))
+        .find(|tok| {
+            // <year>.<minor> with an optional .<patch>
+            let parts: Vec<&str> = tok.split('.').collect();
+            parts[0].len() == 4
+                && (2..=3).contains(&parts.len())
+                && parts[1..]
+                    .iter()
+                    .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod test_toolchain;
@@ -0,0 +1,75 @@
+use super::*;
+
+#[test]
+fn test_parse_version_from_banner() {
+    let banner = "Vivado v2023.2 (64-bit)\nSW Build 4029153 on Fri Oct 13 20:13:54 MDT 2023";
+    assert_eq!(parse_version(banner).as_deref(), Some("2023.2"));
+}
+
+#[test]
+fn test_parse_version_from_path() {
+    let path = "/tools/Xilinx/Vitis/2022.2/settings64.sh";
+    assert_eq!(parse_version(path).as_deref(), Some("2022.2"));
+    assert_eq!(parse_version("/opt/xilinx/bin"), None);
+}
+
+#[test]
+fn test_check_versions() {
+    let cfg = ToolchainCfg {
+        vivado: Some("2023.2".to_string()),
+        ..Default::default()
+    };
+
+    let mut tools = ToolRegistry::default();
+    assert!(
+        tools.check_versions(&cfg).is_err(),
+        "missing tool must mismatch"
+    );
+
+    tools.vivado = Some(ToolInfo {
+        tool: Tool::Vivado,
+        exe: PathBuf::from("vivado"),
+        root: None,
+        version: Some("2023.2".to_string()),
+    });
+    assert!(tools.check_versions(&cfg).is_ok());
+
+    tools.vivado.as_mut().unwrap().version = Some("2022.2".to_string());
+    assert!(tools.check_versions(&cfg).is_err());
+}
+
+#[test]
+fn test_parse_patch_version() {
+    assert_eq!(
+        parse_version("Vivado v2023.2.1 (64-bit)").as_deref(),
+        Some("2023.2.1")
+    );
+    assert_eq!(parse_version("Vivado v2023.2.").as_deref(), Some("2023.2"));
+    assert_eq!(parse_version("build 2023.2.1.4"), None);
+    assert!(version_matches("2023.2.1", "2023.2"));
+    assert!(!version_matches("2023.21", "2023.2"));
+}
+
+#[test]
+fn test_build_info_escapes_paths() {
+    let path = std::env::temp_dir().join(format!("spinhdl_build_info_{}.toml", std::process::id()));
+    let exe = r#"C:\Xilinx\Vivado "2023.2"\bin\vivado.bat"#;
+    let tools = ToolRegistry {
+        vivado: Some(ToolInfo {
+            tool: Tool::Vivado,
+            exe: PathBuf::from(exe),
+            root: None,
+            version: Some("2023.2.1".to_string()),
+        }),
+        vitis: None,
+    };
+
+    tools.write_build_info(&path).unwrap();
+    let info: toml::Table = toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(info["toolchain"]["vivado_exe"].as_str(), Some(exe));
+    assert_eq!(
+        info["toolchain"]["vivado_version"].as_str(),
+        Some("2023.2.1")
+    );
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,59 @@
+{# Macros shared by the other templates. #}
+{% macro step(cmd, opts) -%}
+{{ cmd }}{% if opts.directive %} -directive {{ opts.directive | tcl }}{% endif %}{% if opts.args %} {{ opts.args | tcl_args }}{% endif %}
+{%- endmacro %}
+
+{# opt, place, the optional phys_opt and route of the open design. #}
+{% macro implement(impl) -%}
+{{ step("opt_design", impl.opt_design) }}
+{{ step("place_design", impl.place_design) }}
+{% if impl.phys_opt_design %}
+{{ step("phys_opt_design", impl.phys_opt_design) }}
+{% endif %}
+{{ step("route_design", impl.route_design) }}
+{%- endmacro %}
+
+{# Utilization, timing, power and DRC reports of the open design, picked up
+   by spinhdl from reports/<prefix>_<kind>.rpt. #}
+{% macro reports(prefix) -%}
+file mkdir reports
+report_utilization -file {{ ("reports/" ~ prefix ~ "_utilization.rpt") | tcl }}
+report_timing_summary -file {{ ("reports/" ~ prefix ~ "_timing_summary.rpt") | tcl }}
+report_power -file {{ ("reports/" ~ prefix ~ "_power.rpt") | tcl }}
+report_drc -file {{ ("reports/" ~ prefix ~ "_drc.rpt") | tcl }}
+{%- endmacro %}
+
+
+{# Properties of the pblock of partition `pr` from its `pblock` table. #}
+{% macro pblock_props(pr) -%}
+{% set pblock = pr.pblock_name | tcl %}
+set_property SNAPPING_MODE {{ pr.pblock.snapping_mode | upper | tcl }} [get_pblocks {{ pblock }}]
+{% if pr.pblock.reset_after_reconfig %}
+set_property RESET_AFTER_RECONFIG 1 [get_pblocks {{ pblock }}]
+{% endif %}
+{% if pr.pblock.contain_routing %}
+set_property CONTAIN_ROUTING 1 [get_pblocks {{ pblock }}]
+{% endif %}
+{% if pr.pblock.exclude_placement %}
+set_property EXCLUDE_PLACEMENT 1 [get_pblocks {{ pblock }}]
+{% endif %}
+{%- endmacro %}
+
+{# Pblock of a partition nested in an RM, created under its parent's pblock
+   once `pr_subdivide` made the partition. #}
+{% macro nested_pblock(pr) -%}
+{% set pblock = pr.pblock_name | tcl %}
+{% set cell = pr.instance_name | tcl %}
+{% if pr.floorplan %}
+read_xdc {{ pr.floorplan | tcl }}
+{% else %}
+if {[llength [get_pblocks -quiet {{ pblock }}]] == 0} {
+  create_pblock {{ pblock }}
+  resize_pblock {{ pblock }} -add {{ pr.ranges | tcl }}
+  set_property PARENT {{ pr.parent_pblock | tcl }} [get_pblocks {{ pblock }}]
+}
+add_cells_to_pblock {{ pblock }} [get_cells [list {{ cell }}]] -clear_locs
+{{ pblock_props(pr) -}}
+set_property HD.RECONFIGURABLE 1 [get_cells {{ cell }}]
+{% endif %}
+{%- endmacro %}
@@ -0,0 +1,19 @@
+{% from "common.tcl" import pblock_props %}
+open_project {{ (design.name ~ ".xpr") | tcl }}
+open_run synth_1 -name synth_1
+close [open {{ ("pr_" ~ design.name ~ ".xdc") | tcl }} a]
+add_files -fileset constrs_1 {{ ("pr_" ~ design.name ~ ".xdc") | tcl }}
+set_property target_constrs_file {{ ("pr_" ~ design.name ~ ".xdc") | tcl }} [current_fileset -constrset]
+{% for pr in partitions if not pr.parent and not pr.floorplan %}
+{% set pblock = pr.pblock_name | tcl %}
+{% set cell = pr.instance_name | tcl %}
+startgroup
+create_pblock {{ pblock }}
+resize_pblock {{ pblock }} -add {{ pr.ranges | tcl }}
+add_cells_to_pblock {{ pblock }} [get_cells [list {{ cell }}]] -clear_locs
+endgroup
+{{ pblock_props(pr) -}}
+set_property HD.RECONFIGURABLE 1 [get_cells {{ cell }}]
+{% endfor %}
+save_constraints -force
+close_project
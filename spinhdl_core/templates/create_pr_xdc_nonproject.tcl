@@ -0,0 +1,16 @@
+{% from "common.tcl" import pblock_props %}
+open_checkpoint {{ (design.name ~ "_synth.dcp") | tcl }}
+{% for pr in partitions if not pr.parent and not pr.floorplan %}
+{% set pblock = pr.pblock_name | tcl %}
+{% set cell = pr.instance_name | tcl %}
+startgroup
+create_pblock {{ pblock }}
+resize_pblock {{ pblock }} -add {{ pr.ranges | tcl }}
+add_cells_to_pblock {{ pblock }} [get_cells [list {{ cell }}]] -clear_locs
+endgroup
+{{ pblock_props(pr) -}}
+set_property HD.RECONFIGURABLE 1 [get_cells {{ cell }}]
+{% endfor %}
+write_xdc -force {{ ("pr_" ~ design.name ~ ".xdc") | tcl }}
+write_checkpoint -force {{ (design.name ~ "_pr.dcp") | tcl }}
+close_design
@@ -0,0 +1,37 @@
+create_project -force -part {{ project.part | tcl }} {{ design.name | tcl }}
+{% if design.rtl_files %}
+add_files -fileset sources_1 {{ design.rtl_files | tcl_args }}
+{% endif %}
+{% if design.sim_files %}
+add_files -fileset sim_1 {{ design.sim_files | tcl_args }}
+{% endif %}
+{% for src in design.rtl %}
+{% if src.library %}
+set_property library {{ src.library | tcl }} [get_files {{ src.path | tcl }}]
+{% endif %}
+{% if src.file_type %}
+set_property file_type {{ src.vivado_type | tcl }} [get_files {{ src.path | tcl }}]
+{% endif %}
+{% if src.used_in %}
+set_property used_in {{ src.used_in | tcl }} [get_files {{ src.path | tcl }}]
+{% endif %}
+{% endfor %}
+set_property top {{ design.top | tcl }} [current_fileset]
+{% if design.define_list %}
+set_property verilog_define {{ design.define_list | tcl }} [current_fileset]
+{% endif %}
+{% if design.generic_list %}
+set_property generic {{ design.generic_list | tcl }} [current_fileset]
+{% endif %}
+{% if design.include_dir_paths %}
+set_property include_dirs {{ design.include_dir_paths | tcl }} [current_fileset]
+{% endif %}
+{% if design.xdc_files %}
+add_files -fileset constrs_1 {{ design.xdc_files | tcl_args }}
+{% endif %}
+{% for file in design.xci_files %}
+import_ip {{ file | tcl }}
+{% endfor %}
+{% for file in design.ip_files %}
+source {{ file | tcl }}
+{% endfor %}
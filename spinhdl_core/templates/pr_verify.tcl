@@ -0,0 +1,2 @@
+file mkdir reports
+pr_verify -full_check -initial {{ initial | tcl }} -additional {{ additional | tcl }} -file {{ ("reports/" ~ report) | tcl }}
@@ -0,0 +1,24 @@
+create_project -in_memory -part {{ project.part | tcl }}
+{# simulation-only files have no place in an in-memory synthesis run #}
+{% for src in design.rtl if src.fileset == "sources_1" %}
+{% if src.kind == "vhdl" or src.kind == "vhdl2008" %}
+read_vhdl{% if src.kind == "vhdl2008" %} -vhdl2008{% endif %}{% if src.library %} -library {{ src.library | tcl }}{% endif %} {{ src.path | tcl }}
+{% elif src.kind == "systemverilog" %}
+read_verilog -sv {{ src.path | tcl }}
+{% elif src.kind == "mem" %}
+read_mem {{ src.path | tcl }}
+{% elif src.kind == "verilog" %}
+read_verilog {{ src.path | tcl }}
+{% else %}
+add_files {{ src.path | tcl }}
+{% endif %}
+{% endfor %}
+{% for file in design.xci_files %}
+read_ip {{ file | tcl }}
+{% endfor %}
+{% for file in design.ip_files %}
+source {{ file | tcl }}
+{% endfor %}
+{% for file in design.xdc_files %}
+read_xdc {{ file | tcl }}
+{% endfor %}
@@ -0,0 +1,28 @@
+open_checkpoint {{ (config.name ~ "_routed.dcp") | tcl }}
+set fh [open {{ build_id_file | tcl }}]
+set build_id [string trim [read $fh]]
+close $fh
+{% if pr.stamp == "userid" %}
+set_property BITSTREAM.CONFIG.USERID 0x$build_id [current_design]
+{% else %}
+set_property BITSTREAM.CONFIG.USR_ACCESS 0x$build_id [current_design]
+{% endif %}
+{% for hook in hooks.pre_bitgen %}
+source {{ hook | tcl }}
+{% endfor %}
+{% if config.shell %}
+{% for m in config.modules %}
+write_bitstream -force -cell {{ m.instance_name | tcl }} {{ m.partial_bit | tcl }}
+{% endfor %}
+{% else %}
+write_bitstream -force -bin_file {{ (config.name ~ ".bit") | tcl }}
+write_debug_probes -force {{ (config.name ~ ".ltx") | tcl }}
+write_hw_platform -fixed -force {{ (config.name ~ ".xsa") | tcl }}
+{% endif %}
+{% for m in config.modules if m.writes_bin %}
+write_cfgmem -force -format BIN -interface {{ pr.interface | tcl }} -loadbit {{ ["up", "0x0", m.partial_bit] | tcl }} {{ m.partial_bin | tcl }}
+{% endfor %}
+{% for hook in hooks.post_bitgen %}
+source {{ hook | tcl }}
+{% endfor %}
+close_design
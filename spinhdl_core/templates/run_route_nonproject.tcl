@@ -0,0 +1,84 @@
+{% from "common.tcl" import implement, reports, nested_pblock %}
+{% set impl = design.impl %}
+{% if config.implements_static %}
+open_checkpoint {{ (design.name ~ ("_pr.dcp" if pr_xdc else "_synth.dcp")) | tcl }}
+{% elif config.shell %}
+open_checkpoint {{ config.shell | tcl }}
+{% elif config.parent_dcp %}
+open_checkpoint {{ config.parent_dcp | tcl }}
+{% else %}
+open_checkpoint {{ static_dcp | tcl }}
+{% endif %}
+{% for hook in hooks.pre_route %}
+source {{ hook | tcl }}
+{% endfor %}
+{% if config.implements_static and config.subdivisions %}
+{# static is routed with the parent partitions greyboxed and locked before
+   they are subdivided #}
+{% for m in config.modules if not m.parent %}
+{% if m.greybox or m.hosts_nested %}
+update_design -cell [get_cells {{ m.instance_name | tcl }}] -black_box
+update_design -cell [get_cells {{ m.instance_name | tcl }}] -buffer_ports
+{% else %}
+read_checkpoint -cell [get_cells {{ m.instance_name | tcl }}] {{ ("../" ~ m.rm ~ ".dcp") | tcl }}
+{% endif %}
+{% endfor %}
+{% for pr in partitions if not pr.parent and pr.floorplan %}
+read_xdc {{ pr.floorplan | tcl }}
+{% endfor %}
+{{ implement(impl) }}
+{% for m in config.modules if not m.parent %}
+update_design -cell [get_cells {{ m.instance_name | tcl }}] -black_box
+{% endfor %}
+lock_design -level routing
+write_checkpoint -force {{ static_dcp | tcl }}
+{% endif %}
+{% for s in config.subdivisions %}
+pr_subdivide -cell {{ s.cell | tcl }} -subcells {{ s.subcells | tcl }} {{ ("../" ~ s.rm ~ ".dcp") | tcl }}
+{% for pr in partitions if pr.instance_name in s.subcells %}
+{{ nested_pblock(pr) -}}
+{% endfor %}
+{% endfor %}
+{% for m in config.modules if not m.hosts_nested %}
+{% if m.greybox %}
+{% if not config.shell %}
+update_design -cell [get_cells {{ m.instance_name | tcl }}] -black_box
+{% endif %}
+update_design -cell [get_cells {{ m.instance_name | tcl }}] -buffer_ports
+{% else %}
+read_checkpoint -cell [get_cells {{ m.instance_name | tcl }}] {{ ("../" ~ m.rm ~ ".dcp") | tcl }}
+{% endif %}
+{% endfor %}
+{% if config.implements_static and not config.subdivisions %}
+{% for pr in partitions if not pr.parent and pr.floorplan %}
+read_xdc {{ pr.floorplan | tcl }}
+{% endfor %}
+{% endif %}
+{{ implement(impl) }}
+{% for hook in hooks.post_route %}
+source {{ hook | tcl }}
+{% endfor %}
+{{ reports("route_" ~ config.name) }}
+report_timing -max_paths {{ project.timing.worst_paths }} -delay_type min_max -sort_by slack -file {{ ("reports/route_" ~ config.name ~ "_timing_paths.rpt") | tcl }}
+write_checkpoint -force {{ (config.name ~ "_routed.dcp") | tcl }}
+{% if config.implements_static and not config.subdivisions %}
+{% if pr.abstract_shell %}
+{% for p in partitions if not p.parent %}
+write_abstract_shell -force -cell {{ p.instance_name | tcl }} {{ p.shell_dcp | tcl }}
+{% endfor %}
+{% endif %}
+{% for m in config.modules if not m.parent %}
+update_design -cell [get_cells {{ m.instance_name | tcl }}] -black_box
+{% endfor %}
+lock_design -level routing
+write_checkpoint -force {{ static_dcp | tcl }}
+{% endif %}
+{% if config.locked_dcp %}
+{# the implemented parents stay, every other partition is emptied #}
+{% for m in config.modules if not m.hosts_nested %}
+update_design -cell [get_cells {{ m.instance_name | tcl }}] -black_box
+{% endfor %}
+lock_design -level routing
+write_checkpoint -force {{ config.locked_dcp | tcl }}
+{% endif %}
+close_design
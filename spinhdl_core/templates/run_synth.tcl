@@ -0,0 +1,41 @@
+{% from "common.tcl" import reports %}
+{% set synth = design.synth %}
+open_project {{ (design.name ~ ".xpr") | tcl }}
+{% for hook in hooks.pre_synth %}
+source {{ hook | tcl }}
+{% endfor %}
+{% if design.moduletype == "recon" %}
+synth_design -mode out_of_context
+{%- if synth.flatten_hierarchy %} -flatten_hierarchy {{ synth.flatten_hierarchy | tcl }}{% endif %}
+{%- if synth.directive %} -directive {{ synth.directive | tcl }}{% endif %}
+{%- if synth.args %} {{ synth.args | tcl_args }}{% endif %}
+
+{% for hook in hooks.post_synth %}
+source {{ hook | tcl }}
+{% endfor %}
+{{ reports("synth") }}
+write_checkpoint -force {{ (design.name ~ "/runs/synth_1/" ~ design.name ~ ".dcp") | tcl }}
+close_project
+{% else %}
+reset_run synth_1
+{% if synth.strategy %}
+set_property strategy {{ synth.strategy | tcl }} [get_runs synth_1]
+{% endif %}
+{% if synth.flatten_hierarchy %}
+set_property STEPS.SYNTH_DESIGN.ARGS.FLATTEN_HIERARCHY {{ synth.flatten_hierarchy | tcl }} [get_runs synth_1]
+{% endif %}
+{% if synth.directive %}
+set_property STEPS.SYNTH_DESIGN.ARGS.DIRECTIVE {{ synth.directive | tcl }} [get_runs synth_1]
+{% endif %}
+{% if synth.args %}
+set_property -name {STEPS.SYNTH_DESIGN.ARGS.MORE OPTIONS} -value {{ synth.args | tcl_args | tcl }} -objects [get_runs synth_1]
+{% endif %}
+launch_runs -jobs {{ synth.jobs }} synth_1
+wait_on_run synth_1
+open_run synth_1 -name synth_1
+{% for hook in hooks.post_synth %}
+source {{ hook | tcl }}
+{% endfor %}
+{{ reports("synth") }}
+close_design
+{% endif %}
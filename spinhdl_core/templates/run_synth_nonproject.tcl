@@ -0,0 +1,21 @@
+{% from "common.tcl" import reports %}
+{% set synth = design.synth %}
+source read_sources.tcl
+{% for hook in hooks.pre_synth %}
+source {{ hook | tcl }}
+{% endfor %}
+synth_design -top {{ design.top | tcl }} -part {{ project.part | tcl }}
+{%- if design.moduletype == "recon" %} -mode out_of_context{% endif %}
+{%- if synth.flatten_hierarchy %} -flatten_hierarchy {{ synth.flatten_hierarchy | tcl }}{% endif %}
+{%- if synth.directive %} -directive {{ synth.directive | tcl }}{% endif %}
+{%- for define in design.define_list %} -verilog_define {{ define | tcl }}{% endfor %}
+{%- for generic in design.generic_list %} -generic {{ generic | tcl }}{% endfor %}
+{%- if design.include_dir_paths %} -include_dirs {{ design.include_dir_paths | tcl }}{% endif %}
+{%- if synth.args %} {{ synth.args | tcl_args }}{% endif %}
+
+{% for hook in hooks.post_synth %}
+source {{ hook | tcl }}
+{% endfor %}
+{{ reports("synth") }}
+write_checkpoint -force {{ (design.name ~ "_synth.dcp") | tcl }}
+close_design
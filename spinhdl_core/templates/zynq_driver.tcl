@@ -0,0 +1,78 @@
+source {{ (vitis_root ~ "/scripts/vitis/util/zynqmp_utils.tcl") | tcl }}
+
+# Auto-generated TCL for ZynqMP Vitis driver flow
+
+proc build {dir} {
+    set xsa [file join $dir logic_1.xsa]
+    puts "dir: $dir"
+    setws driver_build/
+    puts "xsa: $xsa"
+    app create -name baremetal_driver -hw $xsa -proc psu_cortexa53_0 -os standalone -lang C -template {Empty Application(C)}
+    importsources -name baremetal_driver -path drivers/baremetal/src/
+    app build -name baremetal_driver hw_server
+}
+
+proc boot_jtag {} {
+    targets -set -filter {name =~ "PSU"}
+    mwr 0xffca0010 0x0    ;# multiboot = 0
+    mwr 0xff5e0200 0x0100 ;# boot mode = JTAG
+    rst -system
+}
+
+proc flash {dir} {
+    connect
+    boot_jtag
+
+    source driver_build/logic_1/hw/psu_init.tcl
+
+    targets -set -nocase -filter {name =~"APU*"}
+    rst -system
+    after 3000
+    targets -set -nocase -filter {name =~"APU*"}
+    reset_apu
+
+    set bitfile [file join $dir logic_1.bit]
+    puts "Programming bitstream: $bitfile"
+    fpga -file $bitfile
+
+    targets -set -nocase -filter {name =~"APU*"}
+    loadhw -hw driver_build/logic_1/hw/logic_1.xsa -mem-ranges [list {0x80000000 0xbfffffff} {0x400000000 0x5ffffffff} {0x1000000000 0x7fffffffff}] -regs
+    configparams force-mem-access 1
+
+    set mode [expr {[mrd -value 0xFF5E0200] & 0xf}]
+
+    targets -set -nocase -filter {name =~ "*A53*#0"}
+    rst -processor
+    dow driver_build/logic_1/export/logic_1/sw/logic_1/boot/fsbl.elf
+    set bp_16_2_fsbl_bp [bpadd -addr &XFsbl_Exit]
+    con -block -timeout 60
+    bpremove $bp_16_2_fsbl_bp
+
+    set part0 [file join $dir logic_1_part.bin]
+    set part1 [file join $dir logic_2_part.bin]
+    set size [file size $part0]
+    mwr -bin -file $part0 0x0800000000 $size
+    mwr -bin -file $part1 0x08000C0000 $size
+
+    rst -processor
+    dow driver_build/baremetal_driver/Debug/baremetal_driver.elf
+    configparams force-mem-access 0
+    bpadd -addr &main
+    con -block -timeout 500
+    con
+}
+
+if {[llength $argv] == 0} {
+    puts "Usage: xsct zynq_driver.tcl <build|flash|all>"
+    exit
+}
+
+set dir {{ dir | tcl }}
+
+set cmd $argv
+switch -- $cmd {
+    build { build $dir }
+    flash { flash $dir }
+    all   { build $dir; flash $dir }
+    default { puts "Unknown argument: $cmd"; exit 1 }
+}